@@ -0,0 +1,236 @@
+//! Compute units consumed by the main instructions.
+//!
+//! Run with `cargo bench` after `cargo build-sbf`; results are written to
+//! `target/benches/compute_units.md` together with the delta against the previous run.
+
+use mollusk_svm::{program, Mollusk};
+use mollusk_svm_bencher::MolluskComputeUnitBencher;
+use native_fundraiser::{
+    instructions::{FundraiserInstructions, InitializeArgs},
+    state::{config::Config, fundraiser::Fundraiser, mint_allowlist::MintAllowlist, AccountDiscriminator}
+};
+use solana_sdk::{
+    account::{
+        AccountSharedData, 
+        WritableAccount
+    }, 
+    instruction::{
+        AccountMeta, 
+        Instruction
+    }, 
+    program_option::COption, 
+    program_pack::Pack, 
+    pubkey::Pubkey
+};
+
+const AMOUNT_TO_RAISE: u64 = 10_000_000;
+const CONTRIBUTION: u64 = 1_000_000;
+
+fn mint_account(mollusk: &Mollusk, token_program: &Pubkey) -> AccountSharedData {
+    let mut account = AccountSharedData::new(
+        mollusk.sysvars.rent.minimum_balance(spl_token::state::Mint::LEN), 
+        spl_token::state::Mint::LEN,
+        token_program
+    );
+    spl_token::state::Mint {
+        mint_authority: COption::None,
+        supply: 100_000_000_000,
+        decimals: 6,
+        is_initialized: true,
+        freeze_authority: COption::None
+    }.pack_into_slice(account.data_as_mut_slice());
+
+    account
+}
+
+fn token_account(
+    mollusk: &Mollusk,
+    token_program: &Pubkey,
+    mint: &Pubkey,
+    owner: &Pubkey,
+    amount: u64
+) -> AccountSharedData {
+    let mut account = AccountSharedData::new(
+        mollusk.sysvars.rent.minimum_balance(spl_token::state::Account::LEN), 
+        spl_token::state::Account::LEN,
+        token_program
+    );
+    spl_token::state::Account {
+        mint: *mint,
+        owner: *owner,
+        amount,
+        delegate: COption::None,
+        state: spl_token::state::AccountState::Initialized,
+        is_native: COption::None,
+        delegated_amount: 0,
+        close_authority: COption::None
+    }.pack_into_slice(account.data_as_mut_slice());
+
+    account
+}
+
+fn config_account(mollusk: &Mollusk, program_id: &Pubkey, bump: u8) -> AccountSharedData {
+    let mut account = AccountSharedData::new(
+        mollusk.sysvars.rent.minimum_balance(Config::LEN), 
+        Config::LEN,
+        program_id
+    );
+    account.set_data_from_slice(&borsh::to_vec(&Config {
+        discriminator: AccountDiscriminator::Config as u8,
+        version: Config::VERSION,
+        admin: Pubkey::new_unique(),
+        paused: false,
+        fee_bps: 0,
+        fee_recipient: Pubkey::new_unique(),
+        max_duration: 0,
+        max_amount_to_raise: 0,
+        bump,
+        rejected_extensions: 0,
+    }).unwrap());
+
+    account
+}
+
+fn allowlist_account(mollusk: &Mollusk, program_id: &Pubkey, mint: &Pubkey, bump: u8) -> AccountSharedData {
+    let mut account = AccountSharedData::new(
+        mollusk.sysvars.rent.minimum_balance(MintAllowlist::LEN), 
+        MintAllowlist::LEN,
+        program_id
+    );
+    account.set_data_from_slice(&borsh::to_vec(&MintAllowlist {
+        discriminator: AccountDiscriminator::MintAllowlist as u8,
+        version: MintAllowlist::VERSION,
+        count: 1,
+        mints: core::array::from_fn(|i| if i == 0 { *mint } else { Pubkey::default() }),
+        bump,
+    }).unwrap());
+
+    account
+}
+
+fn main() {
+    let program_id = native_fundraiser::ID;
+
+    let mut mollusk = Mollusk::new(&program_id, "target/deploy/native_fundraiser");
+    mollusk_token::token::add_program(&mut mollusk);
+
+    let (token_program, token_program_account) = mollusk_token::token::keyed_account();
+    let (system_program, system_program_account) = program::keyed_account_for_system_program();
+
+    let maker = Pubkey::new_unique();
+    let mint_to_raise = Pubkey::new_unique();
+
+    let (fundraiser, bump) = Pubkey::find_program_address(
+        &[b"fundraiser", maker.as_ref()],
+        &program_id
+    );
+    let (vault, _) = Pubkey::find_program_address(
+        &[b"vault", fundraiser.as_ref()],
+        &program_id
+    );
+    let (config, config_bump) = Pubkey::find_program_address(&[b"config"], &program_id);
+    let (mint_allowlist, mint_allowlist_bump) = Pubkey::find_program_address(&[b"mint_allowlist"], &program_id);
+    let (rent_pool, _) = Pubkey::find_program_address(&[b"rent_pool", fundraiser.as_ref()], &program_id);
+
+    // initialize
+    let initialize_data = borsh::to_vec(
+        &FundraiserInstructions::Initialize(InitializeArgs::new(AMOUNT_TO_RAISE, 0, 1))
+    ).unwrap();
+
+    let initialize_instruction = Instruction::new_with_bytes(
+        program_id, 
+        &initialize_data, 
+        vec![
+            AccountMeta::new(maker, true),
+            AccountMeta::new_readonly(mint_to_raise, false),
+            AccountMeta::new(fundraiser, false),
+            AccountMeta::new(vault, false),
+            AccountMeta::new_readonly(system_program, false),
+            AccountMeta::new_readonly(token_program, false),
+            AccountMeta::new_readonly(config, false),
+            AccountMeta::new_readonly(mint_allowlist, false),
+            AccountMeta::new(rent_pool, false),
+            AccountMeta::new_readonly(spl_associated_token_account::ID, false),
+        ]
+    );
+
+    let initialize_accounts = vec![
+        (maker, AccountSharedData::new(1_000_000_000_000, 0, &system_program)),
+        (mint_to_raise, mint_account(&mollusk, &token_program)),
+        (fundraiser, AccountSharedData::new(0, 0, &system_program)),
+        (vault, AccountSharedData::new(0, 0, &system_program)),
+        (system_program, system_program_account.clone()),
+        (token_program, token_program_account.clone()),
+        (config, config_account(&mollusk, &program_id, config_bump)),
+        (mint_allowlist, allowlist_account(&mollusk, &program_id, &mint_to_raise, mint_allowlist_bump)),
+        (rent_pool, AccountSharedData::new(0, 0, &system_program)),
+        (spl_associated_token_account::ID, AccountSharedData::default()),
+    ];
+
+    // contribute, to an already initialized fundraiser
+    let contributor = Pubkey::new_unique();
+    let contributor_ata = Pubkey::new_unique();
+    // only read for contributions made through an intent
+    let instructions_sysvar = solana_sdk::sysvar::instructions::ID;
+    let (contributor_account, _) = Pubkey::find_program_address(
+        &[b"contributor", fundraiser.as_ref(), contributor.as_ref()],
+        &program_id
+    );
+
+    let mut fundraiser_data = vec![0; Fundraiser::LEN];
+    {
+        let fundraiser_account = Fundraiser::new_mut(&mut fundraiser_data).unwrap();
+        fundraiser_account.maker = maker;
+        fundraiser_account.mint_to_raise = mint_to_raise;
+        fundraiser_account.set_amount_to_raise(AMOUNT_TO_RAISE);
+        fundraiser_account.duration = 1;
+        fundraiser_account.bump = bump;
+        fundraiser_account.min_success_percentage = 100;
+    }
+    let mut fundraiser_account = AccountSharedData::new(
+        mollusk.sysvars.rent.minimum_balance(Fundraiser::LEN), 
+        Fundraiser::LEN, 
+        &program_id
+    );
+    fundraiser_account.set_data_from_slice(&fundraiser_data);
+
+    let contribute_instruction = Instruction::new_with_bytes(
+        program_id, 
+        &borsh::to_vec(&FundraiserInstructions::Contribute { amount: CONTRIBUTION, intent: None, wrap_sol: false }).unwrap(), 
+        vec![
+            AccountMeta::new(contributor, true),
+            AccountMeta::new(contributor_account, false),
+            AccountMeta::new(contributor_ata, false),
+            AccountMeta::new(fundraiser, false),
+            AccountMeta::new(vault, false),
+            AccountMeta::new_readonly(token_program, false),
+            AccountMeta::new_readonly(system_program, false),
+            AccountMeta::new_readonly(config, false),
+            AccountMeta::new_readonly(mint_to_raise, false),
+            AccountMeta::new(rent_pool, false),
+            AccountMeta::new(contributor, true),
+            AccountMeta::new_readonly(instructions_sysvar, false),
+        ]
+    );
+
+    let contribute_accounts = vec![
+        (contributor, AccountSharedData::new(1_000_000_000, 0, &system_program)),
+        (contributor_account, AccountSharedData::new(0, 0, &system_program)),
+        (contributor_ata, token_account(&mollusk, &token_program, &mint_to_raise, &contributor, CONTRIBUTION)),
+        (fundraiser, fundraiser_account),
+        (vault, token_account(&mollusk, &token_program, &mint_to_raise, &fundraiser, 0)),
+        (token_program, token_program_account),
+        (system_program, system_program_account),
+        (config, config_account(&mollusk, &program_id, config_bump)),
+        (mint_to_raise, mint_account(&mollusk, &token_program)),
+        (rent_pool, AccountSharedData::new(0, 0, &system_program)),
+        (instructions_sysvar, AccountSharedData::default()),
+    ];
+
+    MolluskComputeUnitBencher::new(mollusk)
+        .bench(("initialize", &initialize_instruction, &initialize_accounts))
+        .bench(("contribute", &contribute_instruction, &contribute_accounts))
+        .must_pass(true)
+        .out_dir("target/benches")
+        .execute();
+}
@@ -0,0 +1,568 @@
+//! `fundraiser` command-line tool.
+//!
+//! Builds the program's instructions from `FundraiserInstructions` and reads the
+//! `Fundraiser` and `Contributor` accounts through the same zero-copy views the program
+//! uses, so the CLI cannot drift from the on-chain layout.
+
+use std::{error::Error, path::PathBuf};
+
+use borsh::BorshDeserialize;
+use clap::{Parser, Subcommand, ValueEnum};
+use native_fundraiser::{
+    events::{decoder::decode_logs, FundraiserEvent},
+    token::{offchain::resolve_hook_accounts, unpack_account},
+    instructions::{FundraiserInstructions, InitializeArgs},
+    state::{
+        config::Config,
+        contributor::Contributor,
+        fundraiser::{Fundraiser, FundingMode}
+    }
+};
+use serde_json::{json, Value};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    account::from_account,
+    clock::Clock,
+    commitment_config::CommitmentConfig,
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    signature::{read_keypair_file, Keypair, Signature},
+    signer::Signer,
+    system_program,
+    sysvar,
+    transaction::Transaction
+};
+use solana_transaction_status::UiTransactionEncoding;
+use spl_associated_token_account::{
+    get_associated_token_address_with_program_id,
+    instruction::create_associated_token_account_idempotent
+};
+
+type CliResult<T> = Result<T, Box<dyn Error>>;
+
+#[derive(Parser)]
+#[command(name = "fundraiser", version, about = "Create and operate fundraisers")]
+struct Cli {
+    /// RPC endpoint, defaults to a local test validator
+    #[arg(long, short = 'u', global = true, default_value = "http://127.0.0.1:8899")]
+    url: String,
+
+    /// Keypair that signs and pays for the transactions [default: ~/.config/solana/id.json]
+    #[arg(long, short = 'k', global = true)]
+    keypair: Option<PathBuf>,
+
+    /// Simulate the transaction and print its logs instead of sending it
+    #[arg(long, global = true)]
+    dry_run: bool,
+
+    #[arg(long, short = 'o', global = true, value_enum, default_value_t = Output::Display)]
+    output: Output,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Output {
+    Display,
+    Json,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Create a fundraiser with the keypair as maker
+    Create {
+        /// Mint of the token being raised
+        #[arg(long)]
+        mint: Pubkey,
+        /// Amount to raise, in base units
+        #[arg(long)]
+        amount: u64,
+        /// Days the fundraiser accepts contributions for
+        #[arg(long)]
+        duration: u8,
+        /// Only approve contributions, they are collected once the fundraiser succeeds
+        #[arg(long)]
+        pledge: bool,
+        /// Let the maker keep whatever is raised
+        #[arg(long)]
+        flexible: bool,
+        /// Percentage of the amount that must be raised for the fundraiser to succeed
+        #[arg(long, default_value_t = 100)]
+        min_success: u8,
+        /// Lamports set aside to pay the rent of contributor accounts
+        #[arg(long, default_value_t = 0)]
+        rent_pool: u64,
+        /// Keep the raised tokens in the fundraiser's associated token account
+        #[arg(long)]
+        ata_vault: bool,
+    },
+    /// Contribute to the fundraiser of `maker`
+    Contribute {
+        #[arg(long)]
+        maker: Pubkey,
+        /// Amount to contribute, in base units
+        #[arg(long)]
+        amount: u64,
+        /// Contribute lamports, wrapped into the vault of a wSOL fundraiser
+        #[arg(long)]
+        wrap_sol: bool,
+    },
+    /// Refund the keypair's contribution to the fundraiser of `maker`
+    Refund {
+        #[arg(long)]
+        maker: Pubkey,
+        /// Receive a wSOL refund as lamports
+        #[arg(long)]
+        unwrap_sol: bool,
+    },
+    /// Pay the raised amount out to the keypair, which must be the maker
+    Payout {
+        /// Receive a wSOL payout as lamports
+        #[arg(long)]
+        unwrap_sol: bool,
+    },
+    /// Show the state of a fundraiser
+    Status {
+        /// Maker of the fundraiser [default: the keypair]
+        #[arg(long)]
+        maker: Option<Pubkey>,
+        /// Also show the contribution of this wallet
+        #[arg(long)]
+        contributor: Option<Pubkey>,
+    },
+}
+
+fn main() {
+    let cli = Cli::parse();
+
+    if let Err(error) = run(cli) {
+        eprintln!("error: {error}");
+        std::process::exit(1);
+    }
+}
+
+fn run(cli: Cli) -> CliResult<()> {
+    let rpc = RpcClient::new_with_commitment(cli.url.clone(), CommitmentConfig::confirmed());
+    let payer = load_keypair(cli.keypair.as_ref())?;
+
+    let fields = match cli.command {
+        Command::Create { mint, amount, duration, pledge, flexible, min_success, rent_pool, ata_vault } => {
+            let fundraiser = fundraiser_address(&payer.pubkey());
+            let token_program = rpc.get_account(&mint)?.owner;
+
+            let args = InitializeArgs {
+                pledge_mode: pledge,
+                funding_mode: if flexible { FundingMode::Flexible } else { FundingMode::AllOrNothing },
+                min_success_percentage: min_success,
+                rent_pool_lamports: rent_pool,
+                ata_vault,
+                ..InitializeArgs::new(amount, cluster_time(&rpc)?, duration)
+            };
+
+            let instruction = instruction(
+                FundraiserInstructions::Initialize(args),
+                vec![
+                    AccountMeta::new(payer.pubkey(), true),
+                    AccountMeta::new_readonly(mint, false),
+                    AccountMeta::new(fundraiser, false),
+                    AccountMeta::new(Fundraiser::vault_address(&fundraiser, &mint, &token_program, ata_vault), false),
+                    AccountMeta::new_readonly(system_program::ID, false),
+                    AccountMeta::new_readonly(token_program, false),
+                    AccountMeta::new_readonly(config_address(), false),
+                    AccountMeta::new_readonly(
+                        Pubkey::find_program_address(&[b"mint_allowlist"], &native_fundraiser::ID).0,
+                        false
+                    ),
+                    AccountMeta::new(rent_pool_address(&fundraiser), false),
+                    AccountMeta::new_readonly(spl_associated_token_account::ID, false),
+                ]
+            )?;
+
+            let mut fields = send(&rpc, &payer, &[instruction], cli.dry_run)?;
+            fields.insert(0, ("fundraiser", json!(fundraiser.to_string())));
+            fields
+        },
+        Command::Contribute { maker, amount, wrap_sol } => {
+            let fundraiser = fundraiser_address(&maker);
+            let fundraiser_account = fetch_fundraiser(&rpc, &fundraiser)?;
+            let mint = fundraiser_account.mint_to_raise;
+            let token_program = rpc.get_account(&mint)?.owner;
+            let vault = vault_address(&fundraiser, &fundraiser_account, &token_program);
+            let contributor_ata = get_associated_token_address_with_program_id(&payer.pubkey(), &mint, &token_program);
+
+            let mut accounts = vec![
+                AccountMeta::new(payer.pubkey(), true),
+                AccountMeta::new(contributor_address(&fundraiser, &payer.pubkey()), false),
+                AccountMeta::new(contributor_ata, false),
+                AccountMeta::new(fundraiser, false),
+                AccountMeta::new(vault, false),
+                AccountMeta::new_readonly(token_program, false),
+                AccountMeta::new_readonly(system_program::ID, false),
+                AccountMeta::new_readonly(config_address(), false),
+                AccountMeta::new_readonly(mint, false),
+                AccountMeta::new(rent_pool_address(&fundraiser), false),
+                AccountMeta::new(payer.pubkey(), true),
+                AccountMeta::new_readonly(sysvar::instructions::ID, false),
+            ];
+
+            accounts.extend(hook_accounts(
+                &rpc,
+                &mint,
+                &[(contributor_ata, vault, payer.pubkey(), amount)]
+            )?);
+
+            let instruction = instruction(FundraiserInstructions::Contribute { amount, intent: None, wrap_sol }, accounts)?;
+
+            send(&rpc, &payer, &[instruction], cli.dry_run)?
+        },
+        Command::Refund { maker, unwrap_sol } => {
+            let fundraiser = fundraiser_address(&maker);
+            let fundraiser_account = fetch_fundraiser(&rpc, &fundraiser)?;
+            let mint = fundraiser_account.mint_to_raise;
+            let token_program = rpc.get_account(&mint)?.owner;
+            let vault = vault_address(&fundraiser, &fundraiser_account, &token_program);
+
+            let contributor_account = contributor_address(&fundraiser, &payer.pubkey());
+            let contributor_ata = get_associated_token_address_with_program_id(&payer.pubkey(), &mint, &token_program);
+            let contributor_data = *Contributor::load(&rpc.get_account_data(&contributor_account)?)?;
+
+            let mut accounts = vec![
+                AccountMeta::new(payer.pubkey(), true),
+                AccountMeta::new(contributor_account, false),
+                AccountMeta::new(contributor_ata, false),
+                AccountMeta::new(fundraiser, false),
+                AccountMeta::new(vault, false),
+                AccountMeta::new_readonly(token_program, false),
+                AccountMeta::new_readonly(mint, false),
+                AccountMeta::new(contributor_data.rent_payer, false),
+                AccountMeta::new_readonly(system_program::ID, false),
+                AccountMeta::new_readonly(spl_associated_token_account::ID, false),
+            ];
+
+            // unused optional accounts are passed as the program ID
+            accounts.push(if fundraiser_account.has_milestones() {
+                AccountMeta::new_readonly(milestones_address(&fundraiser), false)
+            } else {
+                unused_account()
+            });
+
+            accounts.push(if unwrap_sol {
+                AccountMeta::new(unwrap_address(&fundraiser, &payer.pubkey()), false)
+            } else {
+                unused_account()
+            });
+
+            accounts.extend(hook_accounts(
+                &rpc,
+                &mint,
+                &[(vault, contributor_ata, fundraiser, contributor_data.amount())]
+            )?);
+
+            let instruction = instruction(FundraiserInstructions::Refund { unwrap_sol }, accounts)?;
+
+            send(&rpc, &payer, &[instruction], cli.dry_run)?
+        },
+        Command::Payout { unwrap_sol } => {
+            let fundraiser = fundraiser_address(&payer.pubkey());
+            let fundraiser_account = fetch_fundraiser(&rpc, &fundraiser)?;
+            let mint = fundraiser_account.mint_to_raise;
+            let token_program = rpc.get_account(&mint)?.owner;
+            let vault = vault_address(&fundraiser, &fundraiser_account, &token_program);
+
+            let fee_recipient = Config::try_from_slice(&rpc.get_account_data(&config_address())?)?.fee_recipient;
+            let fee_account = get_associated_token_address_with_program_id(&fee_recipient, &mint, &token_program);
+
+            let maker_ata = get_associated_token_address_with_program_id(&payer.pubkey(), &mint, &token_program);
+
+            let mut accounts = vec![
+                AccountMeta::new(payer.pubkey(), true),
+                AccountMeta::new(maker_ata, false),
+                AccountMeta::new(fundraiser, false),
+                AccountMeta::new(vault, false),
+                AccountMeta::new_readonly(token_program, false),
+                AccountMeta::new_readonly(config_address(), false),
+                AccountMeta::new(fee_account, false),
+                AccountMeta::new(mint, false),
+                AccountMeta::new_readonly(system_program::ID, false),
+                AccountMeta::new_readonly(spl_associated_token_account::ID, false),
+            ];
+
+            // unused optional accounts are passed as the program ID
+            accounts.push(if fundraiser_account.has_milestones() {
+                AccountMeta::new(milestones_address(&fundraiser), false)
+            } else if fundraiser_account.stream_duration() > 0 {
+                AccountMeta::new(stream_address(&fundraiser), false)
+            } else {
+                unused_account()
+            });
+
+            accounts.push(if unwrap_sol {
+                AccountMeta::new(unwrap_address(&fundraiser, &payer.pubkey()), false)
+            } else {
+                unused_account()
+            });
+
+            // the program splits what the vault holds into the fee and the payout, milestones
+            // and streams keep the payout in the vault
+            let vault_balance = unpack_account(&rpc.get_account_data(&vault)?)?.amount;
+            let fee = Config::fee(fundraiser_account.fee_bps(), vault_balance);
+
+            let mut transfers = vec![];
+
+            if fee > 0 {
+                transfers.push((vault, fee_account, fundraiser, fee));
+            }
+
+            if !fundraiser_account.has_milestones() && fundraiser_account.stream_duration() == 0 && !unwrap_sol {
+                transfers.push((vault, maker_ata, fundraiser, vault_balance - fee));
+            }
+
+            accounts.extend(hook_accounts(&rpc, &mint, &transfers)?);
+
+            let mut instructions = vec![];
+
+            // the program creates the maker's token account, not the fee recipient's
+            if fundraiser_account.fee_bps() > 0 {
+                instructions.push(create_associated_token_account_idempotent(
+                    &payer.pubkey(),
+                    &fee_recipient,
+                    &mint,
+                    &token_program
+                ));
+            }
+
+            instructions.push(instruction(FundraiserInstructions::Checker { unwrap_sol }, accounts)?);
+
+            send(&rpc, &payer, &instructions, cli.dry_run)?
+        },
+        Command::Status { maker, contributor } => {
+            let fundraiser = fundraiser_address(&maker.unwrap_or(payer.pubkey()));
+            let mut fundraiser_account = fetch_fundraiser(&rpc, &fundraiser)?;
+            fundraiser_account.update_status(cluster_time(&rpc)?)?;
+
+            let mut fields = vec![
+                ("fundraiser", json!(fundraiser.to_string())),
+                ("maker", json!(fundraiser_account.maker.to_string())),
+                ("mint", json!(fundraiser_account.mint_to_raise.to_string())),
+                ("amount_to_raise", json!(fundraiser_account.amount_to_raise())),
+                ("current_amount", json!(fundraiser_account.current_amount())),
+                ("min_success_percentage", json!(fundraiser_account.min_success_percentage)),
+                ("time_started", json!(fundraiser_account.time_started())),
+                ("deadline", json!(fundraiser_account.deadline())),
+                ("status", json!(format!("{:?}", fundraiser_account.status()?))),
+                ("contributor_count", json!(fundraiser_account.contributor_count())),
+                ("funding_mode", json!(format!("{:?}", fundraiser_account.funding_mode()))),
+                ("pledge_mode", json!(fundraiser_account.pledge_mode())),
+                ("milestones", json!(fundraiser_account.has_milestones())),
+                ("stream_duration", json!(fundraiser_account.stream_duration())),
+                ("fee_bps", json!(fundraiser_account.fee_bps())),
+                ("paused", json!(fundraiser_account.paused())),
+                ("mint_extensions", json!(fundraiser_account.mint_extensions())),
+            ];
+
+            if let Some(contributor) = contributor {
+                let contributor_account = contributor_address(&fundraiser, &contributor);
+
+                // a missing account means the wallet never contributed or was refunded
+                match rpc.get_account_data(&contributor_account) {
+                    Ok(data) => {
+                        let contributor_data = *Contributor::load(&data)?;
+
+                        fields.push(("contributed", json!(contributor_data.amount())));
+                        fields.push(("pledge_status", json!(format!("{:?}", contributor_data.pledge_status()))));
+                    },
+                    Err(_) => fields.push(("contributed", json!(0))),
+                }
+            }
+
+            fields
+        },
+    };
+
+    print(cli.output, fields);
+
+    Ok(())
+}
+
+fn load_keypair(path: Option<&PathBuf>) -> CliResult<Keypair> {
+    let path = match path {
+        Some(path) => path.clone(),
+        None => PathBuf::from(std::env::var("HOME")?).join(".config/solana/id.json"),
+    };
+
+    read_keypair_file(&path)
+        .map_err(|error| format!("could not read keypair {}: {error}", path.display()).into())
+}
+
+fn instruction(
+    instruction: FundraiserInstructions,
+    accounts: Vec<AccountMeta>
+) -> CliResult<Instruction> {
+    Ok(Instruction::new_with_bytes(
+        native_fundraiser::ID,
+        &borsh::to_vec(&instruction)?,
+        accounts
+    ))
+}
+
+/// Sends the transaction, or only simulates it on `dry_run`, and returns the fields to
+/// print along with the events it emitted.
+fn send(
+    rpc: &RpcClient,
+    payer: &Keypair,
+    instructions: &[Instruction],
+    dry_run: bool
+) -> CliResult<Vec<(&'static str, Value)>> {
+    let transaction = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&payer.pubkey()),
+        &[payer],
+        rpc.get_latest_blockhash()?
+    );
+
+    let (signature, logs) = if dry_run {
+        let result = rpc.simulate_transaction(&transaction)?.value;
+        let logs = result.logs.unwrap_or_default();
+
+        if let Some(error) = result.err {
+            return Err(format!("simulation failed: {error}\n{}", logs.join("\n")).into())
+        }
+
+        (None, logs)
+    } else {
+        let signature = rpc.send_and_confirm_transaction(&transaction)?;
+
+        (Some(signature), transaction_logs(rpc, &signature)?)
+    };
+
+    let events: Vec<FundraiserEvent> = decode_logs(&logs);
+
+    Ok(vec![
+        ("signature", json!(signature.map(|signature| signature.to_string()))),
+        ("dry_run", json!(dry_run)),
+        ("events", json!(events.iter().map(|event| format!("{event:?}")).collect::<Vec<_>>())),
+    ])
+}
+
+fn transaction_logs(rpc: &RpcClient, signature: &Signature) -> CliResult<Vec<String>> {
+    let transaction = rpc.get_transaction(signature, UiTransactionEncoding::Json)?;
+
+    Ok(transaction.transaction.meta
+        .and_then(|meta| Option::<Vec<String>>::from(meta.log_messages))
+        .unwrap_or_default())
+}
+
+fn print(output: Output, fields: Vec<(&'static str, Value)>) {
+    match output {
+        Output::Json => {
+            let object: serde_json::Map<String, Value> = fields.into_iter()
+                .map(|(key, value)| (key.to_string(), value))
+                .collect();
+
+            println!("{}", Value::Object(object));
+        },
+        Output::Display => {
+            for (key, value) in fields {
+                match value {
+                    Value::String(value) => println!("{key}: {value}"),
+                    Value::Array(values) => {
+                        println!("{key}:");
+                        for value in values {
+                            println!("  {}", value.as_str().map(str::to_string).unwrap_or(value.to_string()));
+                        }
+                    },
+                    value => println!("{key}: {value}"),
+                }
+            }
+        },
+    }
+}
+
+/// Accounts the mint's transfer hook needs for each `(source, destination, authority,
+/// amount)` transfer the instruction makes, without duplicates. Empty without a hook.
+fn hook_accounts(
+    rpc: &RpcClient,
+    mint: &Pubkey,
+    transfers: &[(Pubkey, Pubkey, Pubkey, u64)]
+) -> CliResult<Vec<AccountMeta>> {
+    let mut accounts: Vec<AccountMeta> = vec![];
+
+    for (source, destination, authority, amount) in transfers {
+        let resolved = futures::executor::block_on(resolve_hook_accounts(
+            |address| {
+                let data = rpc.get_account_data(&address).ok();
+                async move { Ok(data) }
+            },
+            mint,
+            source,
+            destination,
+            authority,
+            *amount
+        )).map_err(|error| error as Box<dyn Error>)?;
+
+        for meta in resolved {
+            if !accounts.iter().any(|account| account.pubkey == meta.pubkey) {
+                accounts.push(meta);
+            }
+        }
+    }
+
+    Ok(accounts)
+}
+
+fn cluster_time(rpc: &RpcClient) -> CliResult<i64> {
+    let clock: Clock = from_account(&rpc.get_account(&sysvar::clock::ID)?)
+        .ok_or("could not read the clock sysvar")?;
+
+    Ok(clock.unix_timestamp)
+}
+
+fn fetch_fundraiser(rpc: &RpcClient, address: &Pubkey) -> CliResult<Fundraiser> {
+    let data = rpc.get_account_data(address)
+        .map_err(|_| format!("no fundraiser at {address}"))?;
+
+    Ok(*Fundraiser::load(&data)?)
+}
+
+fn fundraiser_address(maker: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"fundraiser", maker.as_ref()], &native_fundraiser::ID).0
+}
+
+fn config_address() -> Pubkey {
+    Pubkey::find_program_address(&[b"config"], &native_fundraiser::ID).0
+}
+
+fn vault_address(fundraiser: &Pubkey, fundraiser_account: &Fundraiser, token_program: &Pubkey) -> Pubkey {
+    Fundraiser::vault_address(fundraiser, &fundraiser_account.mint_to_raise, token_program, fundraiser_account.ata_vault())
+}
+
+fn contributor_address(fundraiser: &Pubkey, contributor: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"contributor", fundraiser.as_ref(), contributor.as_ref()],
+        &native_fundraiser::ID
+    ).0
+}
+
+fn unwrap_address(fundraiser: &Pubkey, recipient: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"unwrap", fundraiser.as_ref(), recipient.as_ref()],
+        &native_fundraiser::ID
+    ).0
+}
+
+fn milestones_address(fundraiser: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"milestones", fundraiser.as_ref()], &native_fundraiser::ID).0
+}
+
+fn stream_address(fundraiser: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"stream", fundraiser.as_ref()], &native_fundraiser::ID).0
+}
+
+fn unused_account() -> AccountMeta {
+    AccountMeta::new_readonly(native_fundraiser::ID, false)
+}
+
+fn rent_pool_address(fundraiser: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"rent_pool", fundraiser.as_ref()], &native_fundraiser::ID).0
+}
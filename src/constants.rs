@@ -1,7 +1,8 @@
-pub const AMOUNT_TO_RAISE_OFFSET: usize = 8;
-pub const TIME_STARTED_OFFSET: usize = 16;
-pub const DURATION_OFFSET: usize = 17;
+pub const MAX_MILESTONES: usize = 5;
 
-pub const CONTRIBUTE_AMOUNT_OFFSET: usize = 8;
+pub const MAX_ALLOWED_MINTS: usize = 16;
 
-pub const SECONDS_TO_DAYS: i64 = 86400;
\ No newline at end of file
+pub const SECONDS_TO_DAYS: i64 = 86400;
+
+/// Basis points in a whole, fees are capped at it
+pub const MAX_FEE_BPS: u16 = 10_000;
@@ -11,6 +11,88 @@ pub enum FundraiserError {
     InvalidFundraiserMaker,
     #[error("The amount raised is not enough")]
     AmountRaisedNotEnough,
+    #[error("The fundraiser does not take pledges")]
+    NotPledgeMode,
+    #[error("The milestone configuration is invalid")]
+    InvalidMilestones,
+    #[error("The milestone arbiter is invalid")]
+    InvalidArbiter,
+    #[error("The milestone has not been approved")]
+    MilestoneNotApproved,
+    #[error("The milestone has already been released")]
+    MilestoneAlreadyReleased,
+    #[error("The milestones have been abandoned")]
+    MilestonesAbandoned,
+    #[error("The milestones have not been abandoned")]
+    MilestonesNotAbandoned,
+    #[error("The fundraiser has not been closed yet")]
+    FundraiserNotClosed,
+    #[error("The fundraiser has already been closed")]
+    FundraiserAlreadyClosed,
+    #[error("The fundraiser has been cancelled")]
+    FundraiserCancelled,
+    #[error("Governance is not enabled for this fundraiser")]
+    GovernanceDisabled,
+    #[error("The proposal is invalid")]
+    InvalidProposal,
+    #[error("The voting period has ended")]
+    VotingEnded,
+    #[error("The voting period has not ended yet")]
+    VotingNotEnded,
+    #[error("The proposal has already been executed")]
+    ProposalAlreadyExecuted,
+    #[error("The proposal did not reach quorum")]
+    QuorumNotReached,
+    #[error("The proposal was rejected")]
+    ProposalRejected,
+    #[error("Refunds are disabled for flexible fundraisers")]
+    RefundsDisabled,
+    #[error("The amount raised is below the success threshold")]
+    BelowSuccessThreshold,
+    #[error("The account discriminator is invalid")]
+    InvalidAccountDiscriminator,
+    #[error("The account layout is outdated and must be migrated")]
+    AccountVersionMismatch,
+    #[error("The fundraiser has not started yet")]
+    FundraiserNotStarted,
+    #[error("The fundraiser status does not allow this transition")]
+    InvalidStatusTransition,
+    #[error("The fundraiser succeeded, contributions are not refundable")]
+    FundraiserSucceeded,
+    #[error("The config admin is invalid")]
+    InvalidAdmin,
+    #[error("Contributions and payouts are paused")]
+    Paused,
+    #[error("The fundraiser exceeds the limits set in the config")]
+    ExceedsConfigLimit,
+    #[error("The mint is not owned by the token program")]
+    MintNotOwnedByTokenProgram,
+    #[error("The mint account is not a valid mint")]
+    InvalidMint,
+    #[error("The mint is not initialized")]
+    MintNotInitialized,
+    #[error("The mint is not in the allowlist")]
+    MintNotAllowed,
+    #[error("The mint allowlist is full")]
+    MintAllowlistFull,
+    #[error("The mint has an extension the config rejects")]
+    MintExtensionRejected,
+    #[error("The contributor account is still needed for votes or refunds")]
+    ContributorAccountInUse,
+    #[error("The contribution intent is not signed by the contributor")]
+    InvalidIntent,
+    #[error("The contribution intent has expired")]
+    IntentExpired,
+    #[error("The contribution intent nonce was already used")]
+    IntentReplayed,
+    #[error("SOL can only be wrapped or unwrapped for a native mint fundraiser")]
+    NotNativeMint,
+    #[error("Some pledges have not been collected yet")]
+    PledgesNotCollected,
+    #[error("The contributor contributed after the proposal was created")]
+    ContributedAfterProposal,
+    #[error("The account is not the one that paid the rent")]
+    InvalidRentPayer,
 }
 
 impl From<FundraiserError> for ProgramError {
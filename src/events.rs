@@ -0,0 +1,203 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use shank::ShankType;
+use solana_program::{
+    entrypoint::ProgramResult, log::sol_log_data, pubkey::Pubkey
+};
+
+/// Events emitted through `sol_log_data` on every state change. They are Borsh encoded,
+/// so the first byte of each payload is the variant index below: only append new variants.
+#[derive(Debug, Clone, PartialEq, Eq, BorshDeserialize, BorshSerialize, ShankType)]
+pub enum FundraiserEvent {
+    FundraiserCreated {
+        fundraiser: Pubkey,
+        maker: Pubkey,
+        mint_to_raise: Pubkey,
+        amount_to_raise: u64,
+        time_started: i64,
+        duration: u8,
+    },
+    Contributed {
+        fundraiser: Pubkey,
+        contributor: Pubkey,
+        amount: u64,
+        current_amount: u64,
+    },
+    Refunded {
+        fundraiser: Pubkey,
+        contributor: Pubkey,
+        amount: u64,
+    },
+    /// Funds sent to the maker, by `checker`, `release_milestone` or `claim_stream`
+    Claimed {
+        fundraiser: Pubkey,
+        maker: Pubkey,
+        amount: u64,
+    },
+    /// Contributors can refund regardless of the outcome, either through a governance
+    /// vote or because the milestones were abandoned
+    Cancelled {
+        fundraiser: Pubkey,
+    },
+    PledgeCollected {
+        fundraiser: Pubkey,
+        contributor: Pubkey,
+        amount: u64,
+    },
+    PledgeFailed {
+        fundraiser: Pubkey,
+        contributor: Pubkey,
+        amount: u64,
+    },
+    MilestoneApproved {
+        fundraiser: Pubkey,
+        index: u8,
+    },
+    ProposalCreated {
+        fundraiser: Pubkey,
+        proposal: Pubkey,
+        proposer: Pubkey,
+    },
+    Voted {
+        proposal: Pubkey,
+        contributor: Pubkey,
+        approve: bool,
+        weight: u64,
+    },
+    ProposalExecuted {
+        fundraiser: Pubkey,
+        proposal: Pubkey,
+    },
+    /// The admin paused every fundraiser through the config
+    ProgramPaused {
+        admin: Pubkey,
+    },
+    ProgramUnpaused {
+        admin: Pubkey,
+    },
+    /// The maker paused their fundraiser, refunds stay open
+    FundraiserPaused {
+        fundraiser: Pubkey,
+    },
+    FundraiserUnpaused {
+        fundraiser: Pubkey,
+    },
+    /// The mint has features that can drain or lock the vault, as `token::EXTENSION_*` flags
+    MintExtensionsFlagged {
+        fundraiser: Pubkey,
+        mint_to_raise: Pubkey,
+        extensions: u16,
+    },
+    /// Failed pledges took a succeeded fundraiser back below its threshold, it is now refundable
+    PledgesFellShort {
+        fundraiser: Pubkey,
+        current_amount: u64,
+    },
+    /// The raised funds were locked into milestones, after the fee
+    MilestonesFunded {
+        fundraiser: Pubkey,
+        amount: u64,
+    },
+    /// The raised funds started vesting to the maker, after the fee
+    StreamStarted {
+        fundraiser: Pubkey,
+        stream: Pubkey,
+        amount: u64,
+        duration: i64,
+    },
+    ContributorClosed {
+        fundraiser: Pubkey,
+        contributor: Pubkey,
+    },
+    RentPoolWithdrawn {
+        fundraiser: Pubkey,
+        maker: Pubkey,
+        amount: u64,
+    },
+    MintAllowed {
+        mint: Pubkey,
+    },
+    MintDisallowed {
+        mint: Pubkey,
+    },
+    ConfigInitialized {
+        admin: Pubkey,
+    },
+    /// Emitted on every `update_config`, along with the pause events when the pause changed
+    ConfigUpdated {
+        admin: Pubkey,
+        fee_bps: u16,
+        fee_recipient: Pubkey,
+    },
+    /// `kind` is the `AccountDiscriminator` of the account
+    AccountMigrated {
+        account: Pubkey,
+        kind: u8,
+        version: u8,
+    },
+}
+
+impl FundraiserEvent {
+    #[inline]
+    pub fn emit(&self) -> ProgramResult {
+        let data = borsh::to_vec(self)?;
+
+        sol_log_data(&[&data]);
+
+        Ok(())
+    }
+}
+
+#[cfg(not(target_os = "solana"))]
+pub mod decoder {
+    use base64::{engine::general_purpose::STANDARD, Engine};
+    use borsh::BorshDeserialize;
+
+    use super::FundraiserEvent;
+
+    const PROGRAM_DATA: &str = "Program data: ";
+
+    /// Decodes the events emitted by this program from a transaction's log messages.
+    ///
+    /// Invocations are tracked so `Program data:` lines logged by other programs,
+    /// including ones this program CPIs into, are skipped.
+    pub fn decode_logs<S: AsRef<str>>(logs: &[S]) -> Vec<FundraiserEvent> {
+        let program_id = crate::ID.to_string();
+        let mut invocations: Vec<&str> = vec![];
+        let mut events = vec![];
+
+        for line in logs {
+            let line = line.as_ref();
+
+            if let Some(data) = line.strip_prefix(PROGRAM_DATA) {
+                if invocations.last() == Some(&program_id.as_str()) {
+                    events.extend(decode_data(data));
+                }
+                continue;
+            }
+
+            // `Program log:` and `Program return:` lines share the prefix, program ids never end with ':'
+            match line.split_whitespace().collect::<Vec<_>>().as_slice() {
+                ["Program", id, "invoke", depth] if !id.ends_with(':') && depth.starts_with('[') => {
+                    invocations.push(*id);
+                },
+                ["Program", id, "success"] | ["Program", id, "failed:", ..] if !id.ends_with(':') => {
+                    invocations.pop();
+                },
+                _ => {}
+            }
+        }
+
+        events
+    }
+
+    /// Decodes a single `Program data:` log line, without checking which program logged it.
+    pub fn decode_log(line: &str) -> Option<FundraiserEvent> {
+        decode_data(line.strip_prefix(PROGRAM_DATA)?)
+    }
+
+    fn decode_data(data: &str) -> Option<FundraiserEvent> {
+        let bytes = STANDARD.decode(data.trim()).ok()?;
+
+        FundraiserEvent::try_from_slice(&bytes).ok()
+    }
+}
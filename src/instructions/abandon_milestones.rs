@@ -0,0 +1,64 @@
+use borsh::BorshSerialize;
+use solana_program::{
+    account_info::AccountInfo, 
+    entrypoint::ProgramResult, 
+    program_error::ProgramError
+};
+use crate::{
+    error::FundraiserError, 
+    events::FundraiserEvent, 
+    state::{
+        fundraiser::Fundraiser, 
+        milestones::Milestones
+    }
+};
+
+/// Stops any further release, letting contributors `refund` their share of the
+/// tranches still in the vault. Can be called by the arbiter or the maker.
+pub fn abandon_milestones(
+    accounts: &[AccountInfo]
+) -> ProgramResult {
+    let [
+        authority,
+        fundraiser,
+        milestones,
+    ] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys)
+    };
+
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature)
+    }
+
+    if fundraiser.owner != &crate::ID {
+        return Err(ProgramError::InvalidAccountOwner)
+    }
+
+    let fundraiser_account = *Fundraiser::load(
+        &fundraiser.try_borrow_data()?
+    )?;
+
+    let mut milestones_data = Milestones::load(milestones, fundraiser.key)?;
+
+    if milestones_data.arbiter != *authority.key && fundraiser_account.maker != *authority.key {
+        return Err(FundraiserError::InvalidArbiter.into())
+    }
+
+    if !milestones_data.is_funded() {
+        return Err(FundraiserError::FundraiserNotClosed.into())
+    }
+
+    if milestones_data.abandoned {
+        return Err(FundraiserError::MilestonesAbandoned.into())
+    }
+
+    milestones_data.abandoned = true;
+
+    milestones_data.serialize(&mut *milestones.data.borrow_mut())?;
+
+    FundraiserEvent::Cancelled {
+        fundraiser: *fundraiser.key,
+    }.emit()?;
+
+    Ok(())
+}
@@ -0,0 +1,87 @@
+use borsh::BorshSerialize;
+use solana_program::{
+    account_info::AccountInfo, 
+    entrypoint::ProgramResult, 
+    program::invoke_signed, 
+    program_error::ProgramError, 
+    pubkey::Pubkey, 
+    rent::Rent, 
+    system_instruction::create_account, 
+    system_program, 
+    sysvar::Sysvar
+};
+use crate::{
+    error::FundraiserError, 
+    events::FundraiserEvent, 
+    state::{
+        config::Config, 
+        mint_allowlist::MintAllowlist
+    }
+};
+
+/// Allows fundraisers to be created for `mint`, creating the allowlist on first use.
+pub fn add_allowed_mint(
+    accounts: &[AccountInfo],
+    mint: Pubkey
+) -> ProgramResult {
+    let [
+        admin,
+        config,
+        allowlist,
+        system_program,
+    ] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys)
+    };
+
+    // we check the admin is signer
+    if !admin.is_signer {
+        return Err(ProgramError::MissingRequiredSignature)
+    }
+
+    if !system_program::check_id(system_program.key) {
+        return Err(ProgramError::IncorrectProgramId)
+    }
+
+    if Config::load(config)?.admin != *admin.key {
+        return Err(FundraiserError::InvalidAdmin.into())
+    }
+
+    if allowlist.data_is_empty() {
+        let (allowlist_pda, bump) = Pubkey::find_program_address(
+            &[b"mint_allowlist"], 
+            &crate::ID
+        );
+
+        if allowlist_pda != *allowlist.key {
+            return Err(ProgramError::InvalidSeeds)
+        }
+
+        let create_allowlist_ix = create_account(
+            admin.key, 
+            allowlist.key, 
+            Rent::get()?.minimum_balance(MintAllowlist::LEN), 
+            MintAllowlist::LEN as u64, 
+            &crate::ID
+        );
+
+        invoke_signed(
+            &create_allowlist_ix, 
+            &[admin.clone(), allowlist.clone(), system_program.clone()], 
+            &[&[b"mint_allowlist", &[bump]]]
+        )?;
+
+        MintAllowlist::init(allowlist, bump)?;
+    }
+
+    let mut allowlist_data = MintAllowlist::load(allowlist)?;
+
+    allowlist_data.add(&mint)?;
+
+    allowlist_data.serialize(&mut *allowlist.data.borrow_mut())?;
+
+    FundraiserEvent::MintAllowed {
+        mint,
+    }.emit()?;
+
+    Ok(())
+}
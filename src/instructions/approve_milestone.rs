@@ -0,0 +1,56 @@
+use borsh::BorshSerialize;
+use solana_program::{
+    account_info::AccountInfo, 
+    entrypoint::ProgramResult, 
+    program_error::ProgramError
+};
+use crate::{
+    error::FundraiserError, 
+    events::FundraiserEvent, 
+    state::milestones::Milestones
+};
+
+pub fn approve_milestone(
+    accounts: &[AccountInfo],
+    index: u8
+) -> ProgramResult {
+    let [
+        arbiter,
+        fundraiser,
+        milestones,
+    ] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys)
+    };
+
+    let index = index as usize;
+
+    // we check the arbiter is signer
+    if !arbiter.is_signer {
+        return Err(ProgramError::MissingRequiredSignature)
+    }
+
+    let mut milestones_data = Milestones::load(milestones, fundraiser.key)?;
+
+    if milestones_data.arbiter != *arbiter.key {
+        return Err(FundraiserError::InvalidArbiter.into())
+    }
+
+    if index >= milestones_data.count as usize {
+        return Err(FundraiserError::InvalidMilestones.into())
+    }
+
+    if milestones_data.abandoned {
+        return Err(FundraiserError::MilestonesAbandoned.into())
+    }
+
+    milestones_data.milestones[index].approved = true;
+
+    milestones_data.serialize(&mut *milestones.data.borrow_mut())?;
+
+    FundraiserEvent::MilestoneApproved {
+        fundraiser: *fundraiser.key,
+        index: index as u8,
+    }.emit()?;
+
+    Ok(())
+}
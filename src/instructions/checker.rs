@@ -1,21 +1,25 @@
-use borsh::BorshDeserialize;
+use borsh::BorshSerialize;
 use solana_program::{
     account_info::AccountInfo, 
+    clock::Clock, 
     entrypoint::ProgramResult, 
-    program::invoke_signed, 
+    program::{invoke, invoke_signed}, 
     program_error::ProgramError, 
-    program_pack::Pack, 
-    pubkey::Pubkey
+    pubkey::Pubkey, 
+    rent::Rent, 
+    system_instruction::create_account, 
+    system_program, 
+    sysvar::Sysvar
 };
-use crate::{error::FundraiserError, state::fundraiser::Fundraiser};
-use spl_token::instruction::{
-    transfer, 
-    close_account
+use crate::{error::FundraiserError, events::FundraiserEvent, state::{config::Config, fundraiser::{Fundraiser, FundingMode, LifecycleStatus}, milestones::Milestones, stream::Stream}, token::{self, check_destination, check_token_program, create_ata_idempotent, transfer_checked, unpack_account, unwrap_to, TransferAccounts, UnwrapAccounts}};
+use spl_token_2022::{
+    extension::transfer_fee::instruction::harvest_withheld_tokens_to_mint, 
+    instruction::close_account
 };
 
-
 pub fn checker(
-    accounts: &[AccountInfo]
+    accounts: &[AccountInfo],
+    unwrap_sol: bool
 ) -> ProgramResult {
     // we verify that the target amount has been reached
 
@@ -24,13 +28,23 @@ pub fn checker(
         maker_ata,
         fundraiser,
         vault,
-        token_program
+        token_program,
+        config,
+        fee_account,
+        mint_to_raise,
+        system_program,
+        associated_token_program,
+        milestones_or_stream,
+        unwrap_account,
+        hook_accounts @ ..
     ] = accounts else {
         return Err(ProgramError::NotEnoughAccountKeys)
     };
 
     // check if the token_program had the correct id
-    if !spl_token::check_id(&token_program.key) {
+    check_token_program(token_program.key)?;
+
+    if !system_program::check_id(system_program.key) {
         return Err(ProgramError::IncorrectProgramId)
     }
 
@@ -39,31 +53,71 @@ pub fn checker(
         return Err(ProgramError::MissingRequiredSignature)
     }
 
-    let fundraiser_account = Fundraiser::try_from_slice(
-        &fundraiser.try_borrow_data()?
-    )?;
+    if fundraiser.owner != &crate::ID {
+        return Err(ProgramError::InvalidAccountOwner)
+    }
+
+    let fundraiser_account = Fundraiser::sync(fundraiser, Clock::get()?.unix_timestamp)?;
 
     if fundraiser_account.maker != *maker.key {
         return Err(FundraiserError::InvalidFundraiserMaker.into())
     }
 
-    let vault_balance = spl_token::state::Account::unpack(
+    // we check the mint is the one being raised, transfers need its decimals
+    if fundraiser_account.mint_to_raise != *mint_to_raise.key {
+        return Err(ProgramError::InvalidAccountData)
+    }
+
+    // we check the vault is the fundraiser's, the fundraiser signs the transfer
+    fundraiser_account.check_vault(fundraiser.key, vault, token_program.key)?;
+
+    // milestones and streams keep the funds in the vault, only a payout can be unwrapped
+    if unwrap_sol && (fundraiser_account.has_milestones() || fundraiser_account.stream_duration() > 0) {
+        return Err(ProgramError::InvalidInstructionData)
+    }
+
+    let config_data = Config::load(config)?;
+
+    config_data.require_unpaused(&fundraiser_account)?;
+
+    match fundraiser_account.status()? {
+        LifecycleStatus::Cancelled => return Err(FundraiserError::FundraiserCancelled.into()),
+        LifecycleStatus::Settled => return Err(FundraiserError::FundraiserAlreadyClosed.into()),
+        _ => {},
+    }
+
+    let vault_balance = unpack_account(
         &vault.try_borrow_data()?
     )?.amount;
 
+    // all-or-nothing fundraisers can be paid out as soon as the goal is reached
+    if fundraiser_account.pledge_mode() {
+        // pledges are only collected after the deadline, so the payout waits for it too.
+        // `collect` fails the fundraiser if the collected total falls below the threshold
+        fundraiser_account.require_succeeded()?;
 
-    if fundraiser_account.amount_to_raise < vault_balance {
-        return Err(FundraiserError::AmountRaisedNotEnough.into())
+        if fundraiser_account.pending_pledges() > 0 {
+            return Err(FundraiserError::PledgesNotCollected.into())
+        }
+    } else if fundraiser_account.funding_mode() == FundingMode::AllOrNothing {
+        fundraiser_account.check_success()?;
     }
 
-    let transfer_ix = transfer(
-        token_program.key, 
-        vault.key, 
-        maker_ata.key, 
-        fundraiser.key, 
-        &[], 
-        vault_balance
-    )?;
+    // flexible fundraisers can be withdrawn from while running, the accounts are
+    // only closed once the fundraiser has ended
+    let keep_open = fundraiser_account.funding_mode() == FundingMode::Flexible
+        && fundraiser_account.status()? == LifecycleStatus::Active;
+
+    if !keep_open {
+        let mut data = fundraiser.try_borrow_mut_data()?;
+        let fundraiser_account = Fundraiser::load_mut(&mut data)?;
+
+        if fundraiser_account.status()? == LifecycleStatus::Active {
+            fundraiser_account.transition(LifecycleStatus::Succeeded)?;
+        }
+
+        fundraiser_account.transition(LifecycleStatus::Settled)?;
+    }
 
     let seeds = &[
         b"fundraiser",
@@ -71,16 +125,174 @@ pub fn checker(
         &[fundraiser_account.bump]
     ];
 
-    invoke_signed(
-        &transfer_ix, 
-        &[
-            token_program.clone(),
-            vault.clone(),
-            maker_ata.clone(),
-            fundraiser.clone()
-        ], 
-        &[seeds]
-    )?;
+    // the fee is taken on everything leaving the vault, before it is paid out,
+    // locked into milestones or streamed
+    let fee = Config::fee(fundraiser_account.fee_bps(), vault_balance);
+
+    if fee > 0 {
+        check_destination(fee_account, &config_data.fee_recipient, mint_to_raise.key, token_program.key)?;
+
+        transfer_checked(
+            &TransferAccounts {
+                token_program,
+                source: vault,
+                mint: mint_to_raise,
+                destination: fee_account,
+                authority: fundraiser,
+                hook_accounts,
+            },
+            fee,
+            &[seeds]
+        )?;
+    }
+
+    let payout = vault_balance - fee;
+
+    // with milestones the raised funds stay in the vault and are released tranche by tranche
+    if fundraiser_account.has_milestones() {
+        let milestones = milestones_or_stream;
+
+        let mut milestones_data = Milestones::load(milestones, fundraiser.key)?;
+
+        if milestones_data.is_funded() {
+            return Err(FundraiserError::FundraiserAlreadyClosed.into())
+        }
+
+        milestones_data.total_raised = payout;
+        milestones_data.total_contributed = fundraiser_account.current_amount();
+
+        milestones_data.serialize(&mut *milestones.data.borrow_mut())?;
+
+        FundraiserEvent::MilestonesFunded {
+            fundraiser: *fundraiser.key,
+            amount: payout,
+        }.emit()?;
+
+        return Ok(())
+    }
+
+    // with a stream the raised funds stay in the vault and vest to the maker through `claim_stream`
+    if fundraiser_account.stream_duration() > 0 {
+        let stream = milestones_or_stream;
+
+        if !stream.data_is_empty() {
+            return Err(FundraiserError::FundraiserAlreadyClosed.into())
+        }
+
+        let (stream_pda, stream_bump) = Pubkey::find_program_address(
+            &[b"stream", fundraiser.key.as_ref()], 
+            &crate::ID
+        );
+
+        if stream_pda != *stream.key {
+            return Err(ProgramError::InvalidSeeds)
+        }
+
+        let create_stream_ix = create_account(
+            maker.key, 
+            stream.key, 
+            Rent::get()?.minimum_balance(Stream::LEN), 
+            Stream::LEN as u64, 
+            &crate::ID
+        );
+
+        invoke_signed(
+            &create_stream_ix, 
+            &[maker.clone(), stream.clone(), system_program.clone()], 
+            &[&[b"stream", fundraiser.key.as_ref(), &[stream_bump]]]
+        )?;
+
+        Stream::init(
+            stream, 
+            payout, 
+            Clock::get()?.unix_timestamp, 
+            fundraiser_account.stream_duration(), 
+            stream_bump
+        )?;
+
+        FundraiserEvent::StreamStarted {
+            fundraiser: *fundraiser.key,
+            stream: *stream.key,
+            amount: payout,
+            duration: fundraiser_account.stream_duration(),
+        }.emit()?;
+
+        return Ok(())
+    }
+
+    if unwrap_sol {
+        unwrap_to(
+            &UnwrapAccounts {
+                token_program,
+                vault,
+                mint: mint_to_raise,
+                temporary_account: unwrap_account,
+                fundraiser,
+                recipient: maker,
+                system_program,
+            },
+            payout,
+            seeds
+        )?;
+    } else {
+        // the maker pays for their token account if they do not have one yet
+        if maker_ata.data_is_empty() {
+            create_ata_idempotent(
+                maker, 
+                maker_ata, 
+                maker, 
+                mint_to_raise, 
+                system_program, 
+                token_program, 
+                associated_token_program
+            )?;
+        }
+
+        check_destination(maker_ata, maker.key, mint_to_raise.key, token_program.key)?;
+
+        // a transfer fee mint withholds its fee on the payout, the maker receives the rest
+        transfer_checked(
+            &TransferAccounts {
+                token_program,
+                source: vault,
+                mint: mint_to_raise,
+                destination: maker_ata,
+                authority: fundraiser,
+                hook_accounts,
+            },
+            payout,
+            &[seeds]
+        )?;
+    }
+
+    FundraiserEvent::Claimed {
+        fundraiser: *fundraiser.key,
+        maker: *maker.key,
+        amount: payout,
+    }.emit()?;
+
+    if keep_open {
+        return Ok(())
+    }
+
+    // fees withheld on incoming contributions stay on the vault and block closing it,
+    // harvesting them to the mint is permissionless
+    if token::has_transfer_fee(mint_to_raise)? {
+        let harvest_ix = harvest_withheld_tokens_to_mint(
+            token_program.key, 
+            mint_to_raise.key, 
+            &[vault.key]
+        )?;
+
+        invoke(
+            &harvest_ix, 
+            &[
+                token_program.clone(),
+                mint_to_raise.clone(),
+                vault.clone()
+            ]
+        )?;
+    }
 
     // we close the fundraiser account and vault
     let close_vault_ix = close_account(
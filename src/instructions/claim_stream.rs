@@ -0,0 +1,169 @@
+use borsh::BorshSerialize;
+use solana_program::{
+    account_info::AccountInfo, 
+    clock::Clock, 
+    entrypoint::ProgramResult, 
+    msg, 
+    program::{invoke, invoke_signed}, 
+    program_error::ProgramError, 
+    pubkey::Pubkey, 
+    sysvar::Sysvar
+};
+use crate::{
+    error::FundraiserError, 
+    events::FundraiserEvent, 
+    state::{
+        config::Config, 
+        fundraiser::Fundraiser, 
+        stream::Stream
+    }, 
+    token::{self, check_destination, check_token_program, transfer_checked, TransferAccounts}
+};
+use spl_token_2022::{
+    extension::transfer_fee::instruction::harvest_withheld_tokens_to_mint, 
+    instruction::close_account
+};
+
+/// Sends the maker whatever has vested since the last claim. The claim that empties the
+/// stream closes the vault, the fundraiser and the stream, like `checker` does for a payout.
+pub fn claim_stream(
+    accounts: &[AccountInfo]
+) -> ProgramResult {
+    let [
+        maker,
+        maker_ata,
+        fundraiser,
+        vault,
+        stream,
+        token_program,
+        mint_to_raise,
+        config,
+        hook_accounts @ ..
+    ] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys)
+    };
+
+    // check if the token_program had the correct id
+    check_token_program(token_program.key)?;
+
+    // we check the maker is signer
+    if !maker.is_signer {
+        return Err(ProgramError::MissingRequiredSignature)
+    }
+
+    if fundraiser.owner != &crate::ID {
+        return Err(ProgramError::InvalidAccountOwner)
+    }
+
+    let fundraiser_account = *Fundraiser::load(
+        &fundraiser.try_borrow_data()?
+    )?;
+
+    if fundraiser_account.maker != *maker.key {
+        return Err(FundraiserError::InvalidFundraiserMaker.into())
+    }
+
+    Config::load(config)?.require_unpaused(&fundraiser_account)?;
+
+    // we check the mint is the one being raised, transfers need its decimals
+    if fundraiser_account.mint_to_raise != *mint_to_raise.key {
+        return Err(ProgramError::InvalidAccountData)
+    }
+
+    // we check the vault is the fundraiser's, the fundraiser signs the transfer
+    fundraiser_account.check_vault(fundraiser.key, vault, token_program.key)?;
+
+    // we check the maker receives the payout in their own token account
+    check_destination(maker_ata, maker.key, mint_to_raise.key, token_program.key)?;
+
+    let mut stream_data = Stream::load(stream, fundraiser.key)?;
+
+    let amount = stream_data.claimable(Clock::get()?.unix_timestamp);
+
+    if amount == 0 {
+        msg!("nothing to claim yet");
+        return Ok(())
+    }
+
+    let seeds = &[
+        b"fundraiser",
+        maker.key.as_ref(),
+        &[fundraiser_account.bump]
+    ];
+
+    transfer_checked(
+        &TransferAccounts {
+            token_program,
+            source: vault,
+            mint: mint_to_raise,
+            destination: maker_ata,
+            authority: fundraiser,
+            hook_accounts,
+        },
+        amount,
+        &[seeds]
+    )?;
+
+    stream_data.claimed += amount;
+
+    stream_data.serialize(&mut *stream.data.borrow_mut())?;
+
+    FundraiserEvent::Claimed {
+        fundraiser: *fundraiser.key,
+        maker: *maker.key,
+        amount,
+    }.emit()?;
+
+    if stream_data.claimed < stream_data.total {
+        return Ok(())
+    }
+
+    // fees withheld on incoming contributions stay on the vault and block closing it,
+    // harvesting them to the mint is permissionless
+    if token::has_transfer_fee(mint_to_raise)? {
+        let harvest_ix = harvest_withheld_tokens_to_mint(
+            token_program.key, 
+            mint_to_raise.key, 
+            &[vault.key]
+        )?;
+
+        invoke(
+            &harvest_ix, 
+            &[
+                token_program.clone(),
+                mint_to_raise.clone(),
+                vault.clone()
+            ]
+        )?;
+    }
+
+    // we close the vault, the fundraiser account and the stream
+    let close_vault_ix = close_account(
+        token_program.key, 
+        vault.key, 
+        maker.key, 
+        fundraiser.key, 
+        &[]
+    )?;
+
+    invoke_signed(
+        &close_vault_ix, 
+        &[
+            token_program.clone(),
+            vault.clone(),
+            maker.clone(),
+            fundraiser.clone()
+        ], 
+        &[seeds]
+    )?;
+
+    for account in [fundraiser, stream] {
+        let balance = account.lamports();
+        account.realloc(0, false)?;
+        **account.lamports.borrow_mut() = 0;
+        **maker.lamports.borrow_mut() += balance;
+        account.assign(&Pubkey::default());
+    }
+
+    Ok(())
+}
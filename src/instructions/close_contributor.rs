@@ -0,0 +1,75 @@
+use solana_program::{
+    account_info::AccountInfo, 
+    clock::Clock, 
+    entrypoint::ProgramResult, 
+    program_error::ProgramError, 
+    pubkey::Pubkey, 
+    system_program, 
+    sysvar::Sysvar
+};
+use crate::{
+    error::FundraiserError, 
+    events::FundraiserEvent, 
+    state::{
+        contributor::Contributor, 
+        fundraiser::{Fundraiser, LifecycleStatus}
+    }
+};
+
+/// Closes a contributor account once its fundraiser has been paid out, so the rent goes
+/// back to the rent pool or whoever paid it. Anyone can call it since the rent can only
+/// go back to its payer. Accounts of fundraisers with milestones stay open, they
+/// still count for votes and refunds.
+pub fn close_contributor(
+    accounts: &[AccountInfo]
+) -> ProgramResult {
+    let [
+        contributor,
+        contributor_account,
+        fundraiser,
+        rent_payer,
+    ] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys)
+    };
+
+    // we verify that contributor account is owned by this program
+    if contributor_account.owner != &crate::ID {
+        return Err(ProgramError::InvalidAccountOwner)
+    }
+
+    // we verify the contributor_account corresponds to the contributor and the fundraiser
+    let (contributor_pda, _) = Pubkey::find_program_address(
+        &[
+            b"contributor",
+            fundraiser.key.as_ref(),
+            contributor.key.as_ref(),
+        ], 
+        &crate::ID
+    );
+
+    if contributor_pda != *contributor_account.key {
+        return Err(ProgramError::InvalidSeeds)
+    }
+
+    // `checker` closes the fundraiser account once the maker is paid out directly
+    let closed = fundraiser.owner == &system_program::ID && fundraiser.data_is_empty();
+
+    if !closed {
+        if fundraiser.owner != &crate::ID {
+            return Err(ProgramError::InvalidAccountOwner)
+        }
+
+        let fundraiser_account = Fundraiser::sync(fundraiser, Clock::get()?.unix_timestamp)?;
+
+        if fundraiser_account.status()? != LifecycleStatus::Settled || fundraiser_account.has_milestones() {
+            return Err(FundraiserError::ContributorAccountInUse.into())
+        }
+    }
+
+    Contributor::close(contributor_account, rent_payer)?;
+
+    FundraiserEvent::ContributorClosed {
+        fundraiser: *fundraiser.key,
+        contributor: *contributor.key,
+    }.emit()
+}
@@ -0,0 +1,229 @@
+use solana_program::{
+    account_info::AccountInfo, 
+    clock::Clock, 
+    entrypoint::ProgramResult, 
+    program_error::ProgramError, 
+    program_option::COption, 
+    pubkey::Pubkey, 
+    sysvar::Sysvar
+};
+use crate::{
+    error::FundraiserError, 
+    events::FundraiserEvent, 
+    state::{
+        config::Config, 
+        contributor::{Contributor, PledgeStatus}, 
+        fundraiser::{Fundraiser, LifecycleStatus}
+    }, 
+    token::{self, check_token_program, transfer_checked, unpack_account, TransferAccounts}
+};
+
+/// Pulls pledges into the vault through the delegation granted in `contribute`.
+///
+/// Pledges are passed as `[contributor_account, contributor_ata]` pairs after the fixed
+/// accounts, so a large fundraiser can be collected over several transactions. A pledge
+/// that can no longer be honoured (approval revoked or lowered, balance too low, account
+/// frozen) is marked as failed and removed from `current_amount` instead of aborting.
+pub fn collect(
+    accounts: &[AccountInfo]
+) -> ProgramResult {
+    let [
+        maker,
+        fundraiser,
+        vault,
+        token_program,
+        mint_to_raise,
+        config,
+        pledges @ ..
+    ] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys)
+    };
+
+    if pledges.len() % 2 != 0 {
+        return Err(ProgramError::NotEnoughAccountKeys)
+    }
+
+    // check if the token_program had the correct id
+    check_token_program(token_program.key)?;
+
+    // we check the maker is signer
+    if !maker.is_signer {
+        return Err(ProgramError::MissingRequiredSignature)
+    }
+
+    if fundraiser.owner != &crate::ID {
+        return Err(ProgramError::InvalidAccountOwner)
+    }
+
+    let fundraiser_account = Fundraiser::sync(fundraiser, Clock::get()?.unix_timestamp)?;
+
+    if fundraiser_account.maker != *maker.key {
+        return Err(FundraiserError::InvalidFundraiserMaker.into())
+    }
+
+    Config::load(config)?.require_unpaused(&fundraiser_account)?;
+
+    // we check the mint is the one being raised, transfers need its decimals
+    if fundraiser_account.mint_to_raise != *mint_to_raise.key {
+        return Err(ProgramError::InvalidAccountData)
+    }
+
+    // we check the vault is the fundraiser's, the fundraiser signs the transfer
+    fundraiser_account.check_vault(fundraiser.key, vault, token_program.key)?;
+
+    if !fundraiser_account.pledge_mode() {
+        return Err(FundraiserError::NotPledgeMode.into())
+    }
+
+    // pledges are only collected once the fundraiser has ended successfully
+    fundraiser_account.require_succeeded()?;
+
+    let mut failed_amount = 0u64;
+    let mut withheld_amount = 0u64;
+    let mut resolved = 0u64;
+
+    let seeds = &[
+        b"fundraiser",
+        maker.key.as_ref(),
+        &[fundraiser_account.bump]
+    ];
+
+    for pledge in pledges.chunks_exact(2) {
+        let [contributor_account, contributor_ata] = pledge else {
+            unreachable!()
+        };
+
+        if contributor_account.owner != &crate::ID {
+            return Err(ProgramError::InvalidAccountOwner)
+        }
+
+        if contributor_ata.owner != token_program.key {
+            return Err(ProgramError::InvalidAccountOwner)
+        }
+
+        let contributor_account_data = *Contributor::load(
+            &contributor_account.try_borrow_data()?
+        )?;
+
+        // already collected or failed in a previous batch
+        if contributor_account_data.pledge_status() != PledgeStatus::Pending {
+            continue;
+        }
+
+        let token_account = unpack_account(
+            &contributor_ata.try_borrow_data()?
+        )?;
+
+        if token_account.mint != fundraiser_account.mint_to_raise {
+            return Err(ProgramError::InvalidAccountData)
+        }
+
+        // the token account must belong to the contributor who made the pledge
+        let (contributor_pda, _) = Pubkey::find_program_address(
+            &[
+                b"contributor",
+                fundraiser.key.as_ref(),
+                token_account.owner.as_ref(),
+            ], 
+            &crate::ID
+        );
+
+        if contributor_pda != *contributor_account.key {
+            return Err(ProgramError::InvalidSeeds)
+        }
+
+        let amount = contributor_account_data.amount();
+
+        let collectable = token_account.delegate == COption::Some(*fundraiser.key)
+            && token_account.delegated_amount >= amount
+            && token_account.amount >= amount
+            && !token_account.is_frozen();
+
+        if !collectable {
+            failed_amount += amount;
+            resolved += 1;
+            Contributor::load_mut(
+                &mut contributor_account.try_borrow_mut_data()?
+            )?.set_pledge_status(PledgeStatus::Failed);
+
+            FundraiserEvent::PledgeFailed {
+                fundraiser: *fundraiser.key,
+                contributor: token_account.owner,
+                amount,
+            }.emit()?;
+            continue;
+        }
+
+        let vault_balance = token::balance(vault)?;
+
+        // pledges are pairs of accounts, so there is no room for transfer hook accounts,
+        // `initialize` does not allow pledge mode for transfer hook mints
+        transfer_checked(
+            &TransferAccounts {
+                token_program,
+                source: contributor_ata,
+                mint: mint_to_raise,
+                destination: vault,
+                authority: fundraiser,
+                hook_accounts: &[],
+            },
+            amount,
+            &[seeds]
+        )?;
+
+        // a transfer fee mint withholds part of the pledge, the contributor is only
+        // credited what reached the vault so their refund stays covered
+        let received = token::balance(vault)?
+            .checked_sub(vault_balance)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+
+        withheld_amount += amount - received;
+        resolved += 1;
+
+        {
+            let mut data = contributor_account.try_borrow_mut_data()?;
+            let contributor_account_data = Contributor::load_mut(&mut data)?;
+
+            contributor_account_data.set_amount(received);
+            contributor_account_data.set_pledge_status(PledgeStatus::Collected);
+        }
+
+        FundraiserEvent::PledgeCollected {
+            fundraiser: *fundraiser.key,
+            contributor: token_account.owner,
+            amount: received,
+        }.emit()?;
+    }
+
+    let mut data = fundraiser.try_borrow_mut_data()?;
+    let fundraiser_account = Fundraiser::load_mut(&mut data)?;
+
+    let pending_pledges = fundraiser_account.pending_pledges()
+        .checked_sub(resolved)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+
+    fundraiser_account.set_pending_pledges(pending_pledges);
+
+    // failed pledges and withheld fees are taken out of the total, so it matches what
+    // is in the vault
+    if failed_amount + withheld_amount > 0 {
+        let current_amount = fundraiser_account.current_amount()
+            .checked_sub(failed_amount + withheld_amount)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+
+        fundraiser_account.set_current_amount(current_amount);
+
+        // below the threshold the fundraiser fails after all, and what was collected
+        // becomes refundable instead of being stuck in the vault
+        if fundraiser_account.check_success().is_err() {
+            fundraiser_account.transition(LifecycleStatus::Failed)?;
+
+            FundraiserEvent::PledgesFellShort {
+                fundraiser: *fundraiser.key,
+                current_amount: fundraiser_account.current_amount(),
+            }.emit()?;
+        }
+    }
+
+    Ok(())
+}
@@ -1,20 +1,27 @@
 use solana_program::{
-    account_info::AccountInfo, clock::Clock, entrypoint::ProgramResult, program::{invoke, invoke_signed}, program_error::ProgramError, pubkey::Pubkey, rent::Rent, system_instruction::create_account, system_program, sysvar::Sysvar
+    account_info::AccountInfo, clock::Clock, entrypoint::ProgramResult, program::{invoke, invoke_signed}, program_error::ProgramError, program_option::COption, pubkey::Pubkey, rent::Rent, system_instruction::{create_account, transfer}, system_program, sysvar::Sysvar
 };
-use borsh::BorshDeserialize;
-use spl_token::instruction::transfer;
+use spl_token_2022::instruction::{approve, sync_native};
 
 use crate::{
-    constants::CONTRIBUTE_AMOUNT_OFFSET, 
+    error::FundraiserError, 
+    events::FundraiserEvent, 
+    instructions::ContributionIntentArgs, 
+    intent::ContributionIntent, 
     state::{
+        config::Config, 
         contributor::Contributor, 
-        fundraiser::Fundraiser
-    }
+        fundraiser::Fundraiser, 
+        rent_pool::RentPool
+    }, 
+    token::{self, check_token_program, transfer_checked, TransferAccounts}
 };
 
 pub fn contribute(
     accounts: &[AccountInfo],
-    instruction_data: &[u8],
+    amount: u64,
+    intent: Option<ContributionIntentArgs>,
+    wrap_sol: bool,
 ) -> ProgramResult {
     let [
         contributor,
@@ -24,52 +31,182 @@ pub fn contribute(
         vault,
         token_program,
         system_program,
+        config,
+        mint_to_raise,
+        rent_pool,
+        payer,
+        instructions_sysvar,
+        hook_accounts @ ..
     ] = accounts else {
         return Err(ProgramError::NotEnoughAccountKeys)
     };
 
-    // transfer into vault
-    let amount = u64::try_from_slice(&instruction_data[..CONTRIBUTE_AMOUNT_OFFSET])?;
-    
-    if !contributor.is_signer {
+    // with an intent the contributor signed off-chain, which is verified below
+    if (intent.is_none() && !contributor.is_signer) || !payer.is_signer {
         return Err(ProgramError::MissingRequiredSignature)
     }
 
     // check program_ids for system_program and token_program
-    if !spl_token::check_id(&token_program.key) {
+    check_token_program(token_program.key)?;
+
+    if !system_program::check_id(system_program.key) {
         return Err(ProgramError::IncorrectProgramId)
     }
 
-    if !system_program::check_id(&system_program.key) {
-        return Err(ProgramError::IncorrectProgramId)
+    if fundraiser.owner != &crate::ID {
+        return Err(ProgramError::InvalidAccountOwner)
+    }
+
+    let clock = Clock::get()?;
+    let current_time = clock.unix_timestamp;
+
+    let fundraiser_account = Fundraiser::sync(fundraiser, current_time)?;
+
+    Config::load(config)?.require_unpaused(&fundraiser_account)?;
+
+    fundraiser_account.require_active()?;
+
+    // we check the mint is the one being raised, transfers need its decimals
+    if fundraiser_account.mint_to_raise != *mint_to_raise.key {
+        return Err(ProgramError::InvalidAccountData)
+    }
+
+    // the amount credited is measured on the vault, so it has to be the fundraiser's
+    fundraiser_account.check_vault(fundraiser.key, vault, token_program.key)?;
+
+    // a zero contribution would only create an empty contributor account, paid by the pool
+    if amount == 0 {
+        return Err(ProgramError::InvalidInstructionData)
+    }
+
+    if wrap_sol {
+        // the lamports come straight from the contributor, a pledge would need wrapped SOL
+        // sitting in their token account
+        if intent.is_some() || fundraiser_account.pledge_mode() {
+            return Err(ProgramError::InvalidInstructionData)
+        }
+
+        if !token::is_native_mint(mint_to_raise.key) {
+            return Err(FundraiserError::NotNativeMint.into())
+        }
     }
 
-    let transfer_ix = transfer(
-        token_program.key, 
-        contributor_ata.key, 
-        vault.key, 
-        contributor.key, 
-        &[], 
+    if let Some(intent) = &intent {
+        // a pledge needs the contributor to sign the approval
+        if fundraiser_account.pledge_mode() {
+            return Err(ProgramError::InvalidInstructionData)
+        }
+
+        if current_time > intent.expires_at {
+            return Err(FundraiserError::IntentExpired.into())
+        }
+
+        // an intent cannot outlive the fundraiser it was signed for, see `intent`
+        if intent.expires_at > fundraiser_account.deadline() {
+            return Err(FundraiserError::InvalidIntent.into())
+        }
+
+        ContributionIntent {
+            program_id: crate::ID,
+            fundraiser: *fundraiser.key,
+            contributor: *contributor.key,
+            amount,
+            nonce: intent.nonce,
+            expires_at: intent.expires_at,
+        }.verify(instructions_sysvar)?;
+
+        let token_account = token::unpack_account(&contributor_ata.try_borrow_data()?)?;
+
+        // the fundraiser pulls the tokens as delegate, only out of the contributor's own account
+        if token_account.owner != *contributor.key {
+            return Err(ProgramError::InvalidAccountData)
+        }
+
+        // the pull uses up the whole approval, so submitting the intent again needs a new
+        // approval from the contributor whatever the nonce stored on their account
+        if token_account.delegate != COption::Some(*fundraiser.key) || token_account.delegated_amount != amount {
+            return Err(FundraiserError::InvalidIntent.into())
+        }
+    }
+
+    // pledges stay in the contributor's token account until they are collected, `collect`
+    // then credits what actually reached the vault
+    let amount = if fundraiser_account.pledge_mode() {
         amount
-    )?;
+    } else {
+        // lamports sent to a native vault are only counted once synced, syncing first keeps
+        // stray lamports out of this contribution
+        if wrap_sol {
+            invoke(
+                &sync_native(token_program.key, vault.key)?, 
+                &[vault.clone(), token_program.clone()]
+            )?;
+        }
 
-    invoke(
-        &transfer_ix, 
-        &[
-            token_program.clone(),
-            contributor_ata.clone(),
-            vault.clone(),
-            contributor.clone()
-        ]
-    )?;
+        let vault_balance = token::balance(vault)?;
 
-    let current_time = Clock::get()?.unix_timestamp;
+        let fundraiser_seeds: &[&[u8]] = &[
+            b"fundraiser".as_ref(),
+            fundraiser_account.maker.as_ref(),
+            &[fundraiser_account.bump]
+        ];
+
+        if wrap_sol {
+            invoke(
+                &transfer(contributor.key, vault.key, amount), 
+                &[contributor.clone(), vault.clone(), system_program.clone()]
+            )?;
+
+            invoke(
+                &sync_native(token_program.key, vault.key)?, 
+                &[vault.clone(), token_program.clone()]
+            )?;
+        } else if intent.is_some() {
+            // the donor approved the fundraiser as delegate when they signed the intent
+            transfer_checked(
+                &TransferAccounts {
+                    token_program,
+                    source: contributor_ata,
+                    mint: mint_to_raise,
+                    destination: vault,
+                    authority: fundraiser,
+                    hook_accounts,
+                },
+                amount,
+                &[fundraiser_seeds]
+            )?;
+        } else {
+            transfer_checked(
+                &TransferAccounts {
+                    token_program,
+                    source: contributor_ata,
+                    mint: mint_to_raise,
+                    destination: vault,
+                    authority: contributor,
+                    hook_accounts,
+                },
+                amount,
+                &[]
+            )?;
+        }
+
+        // a transfer fee mint withholds part of the amount, only what arrived is credited
+        token::balance(vault)?
+            .checked_sub(vault_balance)
+            .ok_or(ProgramError::ArithmeticOverflow)?
+    };
+
+    // a fee can withhold the whole amount, nothing is left to contribute
+    if amount == 0 {
+        return Err(ProgramError::InvalidInstructionData)
+    }
 
     // increase amount in Fundraiser account
-    Fundraiser::increase_amount(fundraiser, amount, current_time)?;
+    Fundraiser::increase_amount(fundraiser, amount)?;
 
-    // increase amount in Contributor account, initializing if necessary
-    if contributor.owner == system_program.key && contributor_account.data_is_empty() {
+    // initialize the Contributor account if necessary, the PDA check below makes sure
+    // it is the contributor's
+    if contributor_account.data_is_empty() {
 
         let (expected_pda, bump) = Pubkey::find_program_address(
             &[
@@ -84,10 +221,18 @@ pub fn contribute(
             return Err(ProgramError::InvalidSeeds)
         }
 
-        let lamports = Rent::get()?.minimum_balance(Contributor::LEN);
+        let rent = Rent::get()?;
+        let lamports = rent.minimum_balance(Contributor::LEN);
+
+        let rent_pool_bump = RentPool::check(rent_pool, fundraiser.key)?;
+
+        // the rent pool pays while it can stay rent exempt, then contributors pay their own
+        let rent_sponsored = rent_pool.lamports() >= lamports + rent.minimum_balance(0);
+
+        let payer = if rent_sponsored { rent_pool } else { payer };
 
         let create_account_ix = create_account(
-            contributor.key, 
+            payer.key, 
             contributor_account.key, 
             lamports, 
             Contributor::LEN as u64, 
@@ -97,25 +242,91 @@ pub fn contribute(
         invoke_signed(
             &create_account_ix, 
             &[
-                contributor.clone(),
+                payer.clone(),
                 contributor_account.clone(),
                 system_program.clone(),
             ], 
-            &[&[
-                b"contributor",
-                fundraiser.key.as_ref(),
-                contributor.key.as_ref(),
-                &[bump]
-            ]]
+            &[
+                &[
+                    b"contributor",
+                    fundraiser.key.as_ref(),
+                    contributor.key.as_ref(),
+                    &[bump]
+                ],
+                &[
+                    b"rent_pool",
+                    fundraiser.key.as_ref(),
+                    &[rent_pool_bump]
+                ],
+            ]
         )?;
 
-        Contributor::init(contributor_account, amount, bump)?;
+        Contributor::init(contributor_account, 0, bump, rent_sponsored, payer.key)?;
 
-        Contributor::increase_amount(contributor_account, amount)?;
+        let mut data = fundraiser.try_borrow_mut_data()?;
+        let fundraiser_account = Fundraiser::load_mut(&mut data)?;
 
-    } else {
-        Contributor::increase_amount(contributor_account, amount)?;
+        fundraiser_account.set_contributor_count(fundraiser_account.contributor_count() + 1);
+
+        // a contributor adding to their pledge still has a single pledge to collect
+        if fundraiser_account.pledge_mode() {
+            fundraiser_account.set_pending_pledges(fundraiser_account.pending_pledges() + 1);
+        }
     }
 
+    // an intent can only be submitted once, the nonces of a contributor keep increasing
+    if let Some(intent) = &intent {
+        let mut data = contributor_account.try_borrow_mut_data()?;
+        let contributor_account_data = Contributor::load_mut(&mut data)?;
+
+        if intent.nonce <= contributor_account_data.intent_nonce() {
+            return Err(FundraiserError::IntentReplayed.into())
+        }
+
+        contributor_account_data.set_intent_nonce(intent.nonce);
+    }
+
+    // increase amount in Contributor account
+    Contributor::increase_amount(contributor_account, amount)?;
+
+    // the contribution only weighs on proposals created after it
+    Contributor::load_mut(
+        &mut contributor_account.try_borrow_mut_data()?
+    )?.set_contributed_slot(clock.slot);
+
+    if fundraiser_account.pledge_mode() {
+        let contributor_account_data = *Contributor::load(
+            &contributor_account.try_borrow_data()?
+        )?;
+
+        // the fundraiser becomes delegate for the whole pledged amount, so it can be
+        // pulled by `collect` once the fundraiser succeeds
+        let approve_ix = approve(
+            token_program.key, 
+            contributor_ata.key, 
+            fundraiser.key, 
+            contributor.key, 
+            &[], 
+            contributor_account_data.amount()
+        )?;
+
+        invoke(
+            &approve_ix, 
+            &[
+                token_program.clone(),
+                contributor_ata.clone(),
+                fundraiser.clone(),
+                contributor.clone()
+            ]
+        )?;
+    }
+
+    FundraiserEvent::Contributed {
+        fundraiser: *fundraiser.key,
+        contributor: *contributor.key,
+        amount,
+        current_amount: fundraiser_account.current_amount() + amount,
+    }.emit()?;
+
     Ok(())
-}
\ No newline at end of file
+}
@@ -0,0 +1,161 @@
+use solana_program::{
+    account_info::AccountInfo, 
+    clock::Clock, 
+    entrypoint::ProgramResult, 
+    program::invoke_signed, 
+    program_error::ProgramError, 
+    pubkey::Pubkey, 
+    rent::Rent, 
+    system_instruction::create_account, 
+    system_program, 
+    sysvar::Sysvar
+};
+use crate::{
+    error::FundraiserError, 
+    events::FundraiserEvent, 
+    state::{
+        contributor::{Contributor, PledgeStatus}, 
+        fundraiser::{Fundraiser, LifecycleStatus}, 
+        proposal::{Proposal, ProposalKind}
+    }
+};
+
+pub fn create_proposal(
+    accounts: &[AccountInfo],
+    kind: ProposalKind,
+    argument: u8
+) -> ProgramResult {
+    let [
+        proposer,
+        fundraiser,
+        proposal,
+        system_program,
+        remaining @ ..
+    ] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys)
+    };
+
+    if !proposer.is_signer {
+        return Err(ProgramError::MissingRequiredSignature)
+    }
+
+    if !system_program::check_id(system_program.key) {
+        return Err(ProgramError::IncorrectProgramId)
+    }
+
+    if fundraiser.owner != &crate::ID {
+        return Err(ProgramError::InvalidAccountOwner)
+    }
+
+    let fundraiser_account = Fundraiser::sync(fundraiser, Clock::get()?.unix_timestamp)?;
+
+    if fundraiser_account.quorum == 0 {
+        return Err(FundraiserError::GovernanceDisabled.into())
+    }
+
+    if fundraiser_account.status()? == LifecycleStatus::Cancelled {
+        return Err(FundraiserError::FundraiserCancelled.into())
+    }
+
+    match kind {
+        // the maker proposes how the campaign moves forward
+        ProposalKind::ReleaseTranche | ProposalKind::ExtendDeadline => {
+            if fundraiser_account.maker != *proposer.key {
+                return Err(FundraiserError::InvalidFundraiserMaker.into())
+            }
+
+            if kind == ProposalKind::ReleaseTranche && !fundraiser_account.has_milestones() {
+                return Err(FundraiserError::InvalidProposal.into())
+            }
+
+            if kind == ProposalKind::ExtendDeadline && argument == 0 {
+                return Err(FundraiserError::InvalidProposal.into())
+            }
+        },
+        // while any contributor can ask for their money back
+        ProposalKind::RefundEveryone => {
+            let [contributor_account, ..] = remaining else {
+                return Err(ProgramError::NotEnoughAccountKeys)
+            };
+
+            if contributor_account.owner != &crate::ID {
+                return Err(ProgramError::InvalidAccountOwner)
+            }
+
+            let (contributor_pda, _) = Pubkey::find_program_address(
+                &[
+                    b"contributor",
+                    fundraiser.key.as_ref(),
+                    proposer.key.as_ref(),
+                ], 
+                &crate::ID
+            );
+
+            if contributor_pda != *contributor_account.key {
+                return Err(ProgramError::InvalidAccountData)
+            }
+
+            let contributor_account_data = *Contributor::load(
+                &contributor_account.try_borrow_data()?
+            )?;
+
+            // failed pledges never made it into the fundraiser
+            if contributor_account_data.amount() == 0
+                || contributor_account_data.pledge_status() == PledgeStatus::Failed
+            {
+                return Err(ProgramError::InvalidAccountData)
+            }
+        },
+    }
+
+    let proposal_id = fundraiser_account.proposal_count().to_le_bytes();
+
+    let (proposal_pda, bump) = Pubkey::find_program_address(
+        &[b"proposal", fundraiser.key.as_ref(), &proposal_id], 
+        &crate::ID
+    );
+
+    if proposal_pda != *proposal.key {
+        return Err(ProgramError::InvalidSeeds)
+    }
+
+    let create_proposal_ix = create_account(
+        proposer.key, 
+        proposal.key, 
+        Rent::get()?.minimum_balance(Proposal::LEN), 
+        Proposal::LEN as u64, 
+        &crate::ID
+    );
+
+    invoke_signed(
+        &create_proposal_ix, 
+        &[
+            proposer.clone(),
+            proposal.clone(),
+            system_program.clone(),
+        ], 
+        &[&[b"proposal", fundraiser.key.as_ref(), &proposal_id, &[bump]]]
+    )?;
+
+    Proposal::init(
+        proposal, 
+        fundraiser.key, 
+        &fundraiser_account, 
+        proposer.key, 
+        kind, 
+        argument, 
+        bump
+    )?;
+
+    Fundraiser::load_mut(
+        &mut fundraiser.try_borrow_mut_data()?
+    )?.set_proposal_count(fundraiser_account.proposal_count() + 1);
+
+    FundraiserEvent::ProposalCreated {
+        fundraiser: *fundraiser.key,
+        proposal: *proposal.key,
+        proposer: *proposer.key,
+    }.emit()?;
+
+    Ok(())
+}
@@ -0,0 +1,136 @@
+use borsh::BorshSerialize;
+use solana_program::{
+    account_info::AccountInfo, 
+    clock::Clock, 
+    entrypoint::ProgramResult, 
+    program_error::ProgramError, 
+    sysvar::Sysvar
+};
+use crate::{
+    error::FundraiserError, 
+    events::FundraiserEvent, 
+    state::{
+        fundraiser::{Fundraiser, LifecycleStatus}, 
+        milestones::Milestones, 
+        proposal::{Proposal, ProposalKind}
+    }
+};
+
+/// Enforces the outcome of a proposal once its voting period is over. Anyone can call it.
+pub fn execute_proposal(
+    accounts: &[AccountInfo]
+) -> ProgramResult {
+    let [
+        fundraiser,
+        proposal,
+        remaining @ ..
+    ] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys)
+    };
+
+    if fundraiser.owner != &crate::ID {
+        return Err(ProgramError::InvalidAccountOwner)
+    }
+
+    // no CPI happens here, so the fundraiser is updated in place
+    let mut fundraiser_data = fundraiser.try_borrow_mut_data()?;
+    let fundraiser_account = Fundraiser::load_mut(&mut fundraiser_data)?;
+
+    let current_time = Clock::get()?.unix_timestamp;
+
+    fundraiser_account.update_status(current_time)?;
+
+    let mut proposal_data = Proposal::load(proposal, fundraiser.key)?;
+
+    if proposal_data.executed {
+        return Err(FundraiserError::ProposalAlreadyExecuted.into())
+    }
+
+    if current_time < proposal_data.voting_ends {
+        return Err(FundraiserError::VotingNotEnded.into())
+    }
+
+    // contributions and refunds made while voting do not move the quorum
+    if !proposal_data.quorum_reached(fundraiser_account.quorum) {
+        return Err(FundraiserError::QuorumNotReached.into())
+    }
+
+    if proposal_data.votes_for <= proposal_data.votes_against {
+        return Err(FundraiserError::ProposalRejected.into())
+    }
+
+    match proposal_data.kind {
+        ProposalKind::ReleaseTranche => {
+            let [milestones, ..] = remaining else {
+                return Err(ProgramError::NotEnoughAccountKeys)
+            };
+
+            let mut milestones_data = Milestones::load(milestones, fundraiser.key)?;
+
+            let index = proposal_data.argument as usize;
+
+            if index >= milestones_data.count as usize {
+                return Err(FundraiserError::InvalidMilestones.into())
+            }
+
+            if milestones_data.abandoned {
+                return Err(FundraiserError::MilestonesAbandoned.into())
+            }
+
+            milestones_data.milestones[index].approved = true;
+
+            milestones_data.serialize(&mut *milestones.data.borrow_mut())?;
+
+            FundraiserEvent::MilestoneApproved {
+                fundraiser: *fundraiser.key,
+                index: proposal_data.argument,
+            }.emit()?;
+        },
+        ProposalKind::ExtendDeadline => {
+            // the outcome of an ended fundraiser is already settled
+            fundraiser_account.require_active()?;
+
+            fundraiser_account.duration = fundraiser_account.duration
+                .checked_add(proposal_data.argument)
+                .ok_or(FundraiserError::InvalidProposal)?;
+        },
+        ProposalKind::RefundEveryone => {
+            // once paid out, only funds still locked into milestones can be handed back
+            if fundraiser_account.status()? == LifecycleStatus::Settled && !fundraiser_account.has_milestones() {
+                return Err(FundraiserError::InvalidStatusTransition.into())
+            }
+
+            fundraiser_account.transition(LifecycleStatus::Cancelled)?;
+
+            FundraiserEvent::Cancelled {
+                fundraiser: *fundraiser.key,
+            }.emit()?;
+
+            // funds already locked into milestones become refundable as well
+            if fundraiser_account.has_milestones() {
+                let [milestones, ..] = remaining else {
+                    return Err(ProgramError::NotEnoughAccountKeys)
+                };
+
+                let mut milestones_data = Milestones::load(milestones, fundraiser.key)?;
+
+                if milestones_data.is_funded() {
+                    milestones_data.abandoned = true;
+
+                    milestones_data.serialize(&mut *milestones.data.borrow_mut())?;
+                }
+            }
+        },
+    }
+
+    proposal_data.executed = true;
+
+    proposal_data.serialize(&mut *proposal.data.borrow_mut())?;
+
+    FundraiserEvent::ProposalExecuted {
+        fundraiser: *fundraiser.key,
+        proposal: *proposal.key,
+    }.emit()?;
+
+    Ok(())
+}
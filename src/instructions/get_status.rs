@@ -0,0 +1,56 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use shank::ShankType;
+use solana_program::{
+    account_info::AccountInfo, 
+    clock::Clock, 
+    entrypoint::ProgramResult, 
+    program::set_return_data, 
+    program_error::ProgramError, 
+    sysvar::Sysvar
+};
+use crate::state::fundraiser::{Fundraiser, FundraiserPhase};
+
+/// Borsh encoded return data of `GetStatus`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, BorshDeserialize, BorshSerialize, ShankType)]
+pub struct FundraiserStatus {
+    pub current_amount: u64,
+    pub amount_to_raise: u64,
+    pub deadline: i64,
+    pub phase: FundraiserPhase,
+    pub contributor_count: u64,
+}
+
+/// Returns the `FundraiserStatus` of a fundraiser through the return data, so other
+/// programs can CPI into it and clients can simulate it. Does not modify any account.
+pub fn get_status(
+    accounts: &[AccountInfo]
+) -> ProgramResult {
+    let [
+        fundraiser,
+    ] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys)
+    };
+
+    if fundraiser.owner != &crate::ID {
+        return Err(ProgramError::InvalidAccountOwner)
+    }
+
+    let mut fundraiser_account = *Fundraiser::load(
+        &fundraiser.try_borrow_data()?
+    )?;
+
+    // the status is only brought up to date on the copy, this instruction writes nothing
+    fundraiser_account.update_status(Clock::get()?.unix_timestamp)?;
+
+    let status = FundraiserStatus {
+        current_amount: fundraiser_account.current_amount(),
+        amount_to_raise: fundraiser_account.amount_to_raise(),
+        deadline: fundraiser_account.deadline(),
+        phase: fundraiser_account.phase()?,
+        contributor_count: fundraiser_account.contributor_count(),
+    };
+
+    set_return_data(&borsh::to_vec(&status)?);
+
+    Ok(())
+}
@@ -0,0 +1,113 @@
+use solana_program::{
+    account_info::AccountInfo, 
+    bpf_loader_upgradeable::{self, UpgradeableLoaderState}, 
+    entrypoint::ProgramResult, 
+    program::invoke_signed, 
+    program_error::ProgramError, 
+    pubkey::Pubkey, 
+    rent::Rent, 
+    system_instruction::create_account, 
+    system_program, 
+    sysvar::Sysvar
+};
+use crate::{
+    constants::MAX_FEE_BPS, 
+    error::FundraiserError, 
+    events::FundraiserEvent, 
+    state::config::Config
+};
+
+use super::ConfigArgs;
+
+/// Creates the config PDA. Only the upgrade authority of the program can call it, so the
+/// config cannot be claimed by someone else between the deployment and its creation.
+pub fn init_config(
+    accounts: &[AccountInfo],
+    args: ConfigArgs
+) -> ProgramResult {
+    let [
+        authority,
+        config,
+        program_data,
+        system_program,
+    ] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys)
+    };
+
+    // we check the authority is signer
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature)
+    }
+
+    // we check the id of system_program
+    if !system_program::check_id(system_program.key) {
+        return Err(ProgramError::IncorrectProgramId)
+    }
+
+    if args.fee_bps > MAX_FEE_BPS {
+        return Err(ProgramError::InvalidInstructionData)
+    }
+
+    let (program_data_pda, _) = Pubkey::find_program_address(
+        &[crate::ID.as_ref()], 
+        &bpf_loader_upgradeable::ID
+    );
+
+    if program_data_pda != *program_data.key {
+        return Err(ProgramError::InvalidSeeds)
+    }
+
+    if program_data.owner != &bpf_loader_upgradeable::ID {
+        return Err(ProgramError::InvalidAccountOwner)
+    }
+
+    // the program data starts with the `ProgramData` tag (3 as a u32), the deployment
+    // slot and the optional upgrade authority
+    {
+        let data = program_data.try_borrow_data()?;
+
+        let upgrade_authority = match data.get(..UpgradeableLoaderState::size_of_programdata_metadata()) {
+            Some(metadata) if metadata[..4] == [3, 0, 0, 0] && metadata[12] == 1 => &metadata[13..45],
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+
+        if upgrade_authority != authority.key.as_ref() {
+            return Err(FundraiserError::InvalidAdmin.into())
+        }
+    }
+
+    let (config_pda, bump) = Pubkey::find_program_address(
+        &[b"config"], 
+        &crate::ID
+    );
+
+    if config_pda != *config.key {
+        return Err(ProgramError::InvalidSeeds)
+    }
+
+    if !config.data_is_empty() {
+        return Err(ProgramError::AccountAlreadyInitialized)
+    }
+
+    let create_config_ix = create_account(
+        authority.key, 
+        config.key, 
+        Rent::get()?.minimum_balance(Config::LEN), 
+        Config::LEN as u64, 
+        &crate::ID
+    );
+
+    invoke_signed(
+        &create_config_ix, 
+        &[authority.clone(), config.clone(), system_program.clone()], 
+        &[&[b"config", &[bump]]]
+    )?;
+
+    Config::init(config, &args, bump)?;
+
+    FundraiserEvent::ConfigInitialized {
+        admin: args.admin,
+    }.emit()?;
+
+    Ok(())
+}
@@ -1,30 +1,37 @@
-use borsh::BorshDeserialize;
 use solana_program::{
     account_info::AccountInfo, 
+    clock::Clock, 
     entrypoint::ProgramResult, 
-    program::invoke_signed, 
+    program::{invoke, invoke_signed}, 
     program_error::ProgramError, 
     program_pack::Pack, 
     pubkey::Pubkey, 
     rent::Rent, 
-    system_instruction::create_account, 
+    system_instruction::{create_account, transfer}, 
     system_program, 
     sysvar::Sysvar,
     msg
 };
-use spl_token::instruction::initialize_account3;
+use spl_token_2022::instruction::initialize_account3;
 use crate::{
-    constants::{
-        AMOUNT_TO_RAISE_OFFSET, 
-        DURATION_OFFSET, 
-        TIME_STARTED_OFFSET
+    constants::MAX_MILESTONES, 
+    error::FundraiserError, 
+    events::FundraiserEvent, 
+    state::{
+        config::Config, 
+        fundraiser::{Fundraiser, FundingMode}, 
+        milestones::Milestones, 
+        mint_allowlist::MintAllowlist, 
+        rent_pool::RentPool
     }, 
-    state::fundraiser::Fundraiser
+    token::{self, check_token_program}
 };
 
+use super::InitializeArgs;
+
 pub fn initialize(
     accounts: &[AccountInfo],
-    instruction_data: &[u8]
+    args: InitializeArgs
 ) -> ProgramResult {
     let [
         maker,
@@ -33,25 +40,87 @@ pub fn initialize(
         vault,
         system_program,
         token_program,
+        config,
+        mint_allowlist,
+        rent_pool,
+        associated_token_program,
+        remaining @ ..
     ] = accounts else {
         return Err(ProgramError::NotEnoughAccountKeys)
     };
 
-    let amount_to_raise = u64::try_from_slice(&instruction_data[..AMOUNT_TO_RAISE_OFFSET])?;
-    let time_started = i64::try_from_slice(&instruction_data[AMOUNT_TO_RAISE_OFFSET..TIME_STARTED_OFFSET])?;
-    let duration = u8::try_from_slice(&instruction_data[TIME_STARTED_OFFSET..DURATION_OFFSET])?;
+    let InitializeArgs {
+        amount_to_raise,
+        time_started,
+        duration,
+        pledge_mode,
+        ref milestone_shares,
+        arbiter,
+        quorum,
+        voting_period,
+        stream_duration,
+        funding_mode,
+        min_success_percentage,
+        rent_pool_lamports,
+        ata_vault,
+    } = args;
+
+    // milestones are optional, when set the raised funds stay in the vault
+    // until each tranche is approved
+    let milestone_count = milestone_shares.len();
+
+    if milestone_count > MAX_MILESTONES {
+        return Err(FundraiserError::InvalidMilestones.into())
+    }
+
+    // a quorum of 0 disables proposals
+    if quorum > 100 || (quorum > 0 && voting_period <= 0) {
+        return Err(ProgramError::InvalidInstructionData)
+    }
 
+    // streaming vests the whole raised amount, so it cannot be combined with milestones
+    if stream_duration < 0 || (stream_duration > 0 && milestone_count > 0) {
+        return Err(ProgramError::InvalidInstructionData)
+    }
+
+    // flexible fundraisers pay out as they go, which rules out anything that holds
+    // the funds until the fundraiser succeeds
+    if funding_mode == FundingMode::Flexible && (pledge_mode || milestone_count > 0 || stream_duration > 0) {
+        return Err(ProgramError::InvalidInstructionData)
+    }
+
+    // nothing to raise would succeed straight away
+    if amount_to_raise == 0 {
+        return Err(ProgramError::InvalidInstructionData)
+    }
+
+    if min_success_percentage == 0 || min_success_percentage > 100 {
+        return Err(ProgramError::InvalidInstructionData)
+    }
+
+    // milestones need someone to approve them, the arbiter or a governance vote
+    if milestone_count > 0 && arbiter == Pubkey::default() && quorum == 0 {
+        return Err(FundraiserError::InvalidArbiter.into())
+    }
+
+    let config_data = Config::load(config)?;
+
+    if config_data.paused {
+        return Err(FundraiserError::Paused.into())
+    }
+
+    // 0 leaves a limit unset
+    if (config_data.max_duration > 0 && duration > config_data.max_duration)
+        || (config_data.max_amount_to_raise > 0 && amount_to_raise > config_data.max_amount_to_raise)
+    {
+        return Err(FundraiserError::ExceedsConfigLimit.into())
+    }
 
     let (fundraiser_pda, bump) = Pubkey::find_program_address(
         &[b"fundraiser", maker.key.as_ref()], 
         &crate::ID
     );
 
-    let (vault_pda, vault_bump) = Pubkey::find_program_address(
-        &[b"vault", fundraiser.key.as_ref()], 
-        &crate::ID
-    );
-    
     // Check that maker is signing
     if !maker.is_signer {
         return Err(ProgramError::MissingRequiredSignature)
@@ -62,10 +131,6 @@ pub fn initialize(
         return Err(ProgramError::InvalidSeeds)
     }
 
-    if &vault_pda != vault.key {
-        return Err(ProgramError::InvalidSeeds)
-    }
-
     // check the fundraiser owner is system program since it has not been initialized
     if *fundraiser.owner != system_program::ID {
         return Err(ProgramError::InvalidAccountOwner)
@@ -77,15 +142,63 @@ pub fn initialize(
     }
 
     // we check token_program id is correct
-    if !spl_token::check_id(token_program.key) {
-        return Err(ProgramError::IncorrectProgramId)
+    check_token_program(token_program.key)?;
+
+    // check the vault is the PDA, or the fundraiser's associated token account with `ata_vault`
+    if Fundraiser::vault_address(fundraiser.key, mint_to_raise.key, token_program.key, ata_vault) != *vault.key {
+        return Err(ProgramError::InvalidSeeds)
     }
 
     // we check the id of system_program
-    if !system_program::check_id(&system_program.key) {
+    if !system_program::check_id(system_program.key) {
         return Err(ProgramError::IncorrectProgramId)
     }
 
+    // we check the mint is a mint of the token program, and one the admin allowed
+    if mint_to_raise.owner != token_program.key {
+        return Err(FundraiserError::MintNotOwnedByTokenProgram.into())
+    }
+
+    let mint_data = mint_to_raise.try_borrow_data()?;
+
+    // Token-2022 mints carry their extensions after the base mint
+    let mint = mint_data.get(..spl_token::state::Mint::LEN)
+        .and_then(|base| spl_token::state::Mint::unpack_unchecked(base).ok())
+        .ok_or(FundraiserError::InvalidMint)?;
+
+    if !mint.is_initialized {
+        return Err(FundraiserError::MintNotInitialized.into())
+    }
+
+    if !MintAllowlist::load(mint_allowlist)?.contains(mint_to_raise.key) {
+        return Err(FundraiserError::MintNotAllowed.into())
+    }
+
+    // we check the mint has no feature the config rejects, the others are recorded on
+    // the fundraiser so contributors can see them
+    let mint_extensions = token::inspect_mint(&mint_data)?;
+
+    if mint_extensions & config_data.rejected_extensions != 0 {
+        return Err(FundraiserError::MintExtensionRejected.into())
+    }
+
+    // `collect` has no room for the accounts a transfer hook needs
+    if pledge_mode && mint_extensions & token::EXTENSION_TRANSFER_HOOK != 0 {
+        return Err(ProgramError::InvalidInstructionData)
+    }
+
+    let vault_len = token::account_len(token_program.key, &mint_data)?;
+
+    drop(mint_data);
+
+    // shares must be non-zero and add up to the whole amount raised
+    if milestone_count > 0 && (
+        milestone_shares.contains(&0)
+            || milestone_shares.iter().map(|share| *share as u16).sum::<u16>() != 100
+    ) {
+        return Err(FundraiserError::InvalidMilestones.into())
+    }
+
     let rent = Rent::get()?;
 
     let minimum_balance = rent.minimum_balance(Fundraiser::LEN);
@@ -110,53 +223,141 @@ pub fn initialize(
         fundraiser, 
         maker.key, 
         mint_to_raise.key, 
-        amount_to_raise, 
-        0, 
-        time_started, 
-        duration, 
-        bump
+        &args, 
+        bump,
+        config_data.fee_bps,
+        mint_extensions
     )?;
 
+    // a fundraiser starting right away is active from the start
+    Fundraiser::sync(fundraiser, Clock::get()?.unix_timestamp)?;
+
     msg!("Fundraiser::init invoked succesfully");
 
-    let vault_len = spl_token::state::Account::LEN;
-    let vault_minimum_balance = rent.minimum_balance(vault_len);
+    if milestone_count > 0 {
+        let [milestones, ..] = remaining else {
+            return Err(ProgramError::NotEnoughAccountKeys)
+        };
 
-    let create_vault_ix = create_account(
-        maker.key, 
-        vault.key, 
-        vault_minimum_balance, 
-        vault_len as u64, 
-        token_program.key
-    );
+        let (milestones_pda, milestones_bump) = Pubkey::find_program_address(
+            &[b"milestones", fundraiser.key.as_ref()], 
+            &crate::ID
+        );
 
-    invoke_signed(
-        &create_vault_ix, 
-        &[maker.clone(), vault.clone(), token_program.clone()],
-        &[&[b"vault", fundraiser.key.as_ref(), &[vault_bump]]]
-    )?;
+        if &milestones_pda != milestones.key {
+            return Err(ProgramError::InvalidSeeds)
+        }
 
-    msg!("create_vault_ix invoked succesfully");
+        let create_milestones_ix = create_account(
+            maker.key, 
+            milestones.key, 
+            rent.minimum_balance(Milestones::LEN), 
+            Milestones::LEN as u64, 
+            &crate::ID
+        );
 
-    let initialize_vault_ix = initialize_account3(
-        token_program.key, 
-        vault.key, 
-        mint_to_raise.key, 
-        fundraiser.key
-    )?;
+        invoke_signed(
+            &create_milestones_ix, 
+            &[maker.clone(), milestones.clone()], 
+            &[&[b"milestones", fundraiser.key.as_ref(), &[milestones_bump]]]
+        )?;
 
-    invoke_signed(
-        &initialize_vault_ix, 
-        &[
-            vault.clone(),
-            mint_to_raise.clone(),
-            fundraiser.clone(),
-            token_program.clone(),
-        ],
-        &[&[b"fundraiser", maker.key.as_ref(), &[bump]]]
-    )?;
-    
-    msg!("initialize_vault_ix invoked succesfully");
+        Milestones::init(milestones, &arbiter, milestone_shares, milestones_bump)?;
+
+        msg!("Milestones::init invoked succesfully");
+    }
+
+    if ata_vault {
+        // anyone can create the fundraiser's associated token account beforehand,
+        // it still belongs to the fundraiser
+        token::create_ata_idempotent(
+            maker, 
+            vault, 
+            fundraiser, 
+            mint_to_raise, 
+            system_program, 
+            token_program, 
+            associated_token_program
+        )?;
+
+        msg!("vault created as the fundraiser's associated token account");
+    } else {
+        let (_, vault_bump) = Pubkey::find_program_address(
+            &[b"vault", fundraiser.key.as_ref()], 
+            &crate::ID
+        );
+
+        let vault_minimum_balance = rent.minimum_balance(vault_len);
+
+        let create_vault_ix = create_account(
+            maker.key, 
+            vault.key, 
+            vault_minimum_balance, 
+            vault_len as u64, 
+            token_program.key
+        );
+
+        invoke_signed(
+            &create_vault_ix, 
+            &[maker.clone(), vault.clone(), token_program.clone()],
+            &[&[b"vault", fundraiser.key.as_ref(), &[vault_bump]]]
+        )?;
+
+        msg!("create_vault_ix invoked succesfully");
+
+        let initialize_vault_ix = initialize_account3(
+            token_program.key, 
+            vault.key, 
+            mint_to_raise.key, 
+            fundraiser.key
+        )?;
+
+        invoke_signed(
+            &initialize_vault_ix, 
+            &[
+                vault.clone(),
+                mint_to_raise.clone(),
+                fundraiser.clone(),
+                token_program.clone(),
+            ],
+            &[&[b"fundraiser", maker.key.as_ref(), &[bump]]]
+        )?;
+        
+        msg!("initialize_vault_ix invoked succesfully");
+    }
+
+    // the pool stays a plain system account, it only has to be rent exempt
+    if rent_pool_lamports > 0 {
+        RentPool::check(rent_pool, fundraiser.key)?;
+
+        if rent_pool_lamports < rent.minimum_balance(0) {
+            return Err(ProgramError::InsufficientFunds)
+        }
+
+        invoke(
+            &transfer(maker.key, rent_pool.key, rent_pool_lamports), 
+            &[maker.clone(), rent_pool.clone(), system_program.clone()]
+        )?;
+
+        msg!("rent pool funded with {} lamports", rent_pool_lamports);
+    }
+
+    FundraiserEvent::FundraiserCreated {
+        fundraiser: *fundraiser.key,
+        maker: *maker.key,
+        mint_to_raise: *mint_to_raise.key,
+        amount_to_raise,
+        time_started,
+        duration,
+    }.emit()?;
+
+    if mint_extensions != 0 {
+        FundraiserEvent::MintExtensionsFlagged {
+            fundraiser: *fundraiser.key,
+            mint_to_raise: *mint_to_raise.key,
+            extensions: mint_extensions,
+        }.emit()?;
+    }
 
     Ok(())
 }
\ No newline at end of file
@@ -0,0 +1,161 @@
+use solana_program::{
+    account_info::AccountInfo, 
+    entrypoint::ProgramResult, 
+    program::invoke, 
+    program_error::ProgramError, 
+    pubkey::Pubkey, 
+    rent::Rent, 
+    system_instruction::transfer, 
+    system_program, 
+    sysvar::Sysvar
+};
+use crate::{
+    events::FundraiserEvent,
+    state::{
+        contributor::Contributor, 
+        fundraiser::Fundraiser, 
+        AccountDiscriminator, 
+        ACCOUNT_HEADER_LEN
+    }
+};
+
+/// Upgrades a `Fundraiser` or `Contributor` created before accounts carried a header to the
+/// current layout, reallocating it in place. The discriminator and version are prepended and
+/// the fields added since are appended zeroed.
+pub fn migrate(
+    accounts: &[AccountInfo],
+    kind: u8
+) -> ProgramResult {
+    let [
+        payer,
+        account,
+        system_program,
+        remaining @ ..
+    ] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys)
+    };
+
+    let kind = AccountDiscriminator::try_from(&kind)?;
+
+    if !payer.is_signer {
+        return Err(ProgramError::MissingRequiredSignature)
+    }
+
+    if !system_program::check_id(system_program.key) {
+        return Err(ProgramError::IncorrectProgramId)
+    }
+
+    if account.owner != &crate::ID {
+        return Err(ProgramError::InvalidAccountOwner)
+    }
+
+    let (old_len, new_len) = match kind {
+        AccountDiscriminator::Fundraiser => (Fundraiser::V0_LEN, Fundraiser::LEN),
+        AccountDiscriminator::Contributor => (Contributor::V0_LEN, Contributor::LEN),
+        _ => return Err(ProgramError::InvalidInstructionData),
+    };
+
+    // accounts from before headers can only be told apart by their length, already
+    // migrated accounts are longer
+    if account.data_len() != old_len {
+        return Err(ProgramError::InvalidAccountData)
+    }
+
+    // without a header the address is the only proof of what the account holds
+    let expected_pda = match kind {
+        AccountDiscriminator::Fundraiser => {
+            let maker = Pubkey::try_from(&account.try_borrow_data()?[..32])
+                .map_err(|_| ProgramError::InvalidAccountData)?;
+
+            Pubkey::find_program_address(
+                &[b"fundraiser", maker.as_ref()], 
+                &crate::ID
+            ).0
+        },
+        _ => {
+            let [fundraiser, contributor, ..] = remaining else {
+                return Err(ProgramError::NotEnoughAccountKeys)
+            };
+
+            Pubkey::find_program_address(
+                &[
+                    b"contributor",
+                    fundraiser.key.as_ref(),
+                    contributor.key.as_ref(),
+                ], 
+                &crate::ID
+            ).0
+        },
+    };
+
+    if expected_pda != *account.key {
+        return Err(ProgramError::InvalidSeeds)
+    }
+
+    // the payer covers the rent for the extra bytes
+    let minimum_balance = Rent::get()?.minimum_balance(new_len);
+
+    if account.lamports() < minimum_balance {
+        let transfer_ix = transfer(
+            payer.key, 
+            account.key, 
+            minimum_balance - account.lamports()
+        );
+
+        invoke(
+            &transfer_ix, 
+            &[
+                payer.clone(),
+                account.clone(),
+                system_program.clone(),
+            ]
+        )?;
+    }
+
+    // fields added since the first release are appended, so they start zeroed
+    account.realloc(new_len, true)?;
+
+    let mut data = account.try_borrow_mut_data()?;
+
+    data.copy_within(..old_len, ACCOUNT_HEADER_LEN);
+    data[0] = kind as u8;
+    data[1] = match kind {
+        AccountDiscriminator::Fundraiser => Fundraiser::VERSION,
+        _ => Contributor::VERSION,
+    };
+
+    if kind == AccountDiscriminator::Fundraiser {
+        Fundraiser::load_mut(&mut data)?.migrate_v0();
+    } else {
+        // contributors from before headers paid their own rent
+        let [fundraiser, contributor, ..] = remaining else {
+            return Err(ProgramError::NotEnoughAccountKeys)
+        };
+
+        Contributor::load_mut(&mut data)?.rent_payer = *contributor.key;
+
+        // they were not counted either, their fundraiser has to be migrated first so
+        // refunds can take them off the count again
+
+        if fundraiser.owner != &crate::ID {
+            return Err(ProgramError::InvalidAccountOwner)
+        }
+
+        let mut fundraiser_data = fundraiser.try_borrow_mut_data()?;
+        let fundraiser_account = Fundraiser::load_mut(&mut fundraiser_data)?;
+
+        let contributor_count = fundraiser_account.contributor_count()
+            .checked_add(1)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+
+        fundraiser_account.set_contributor_count(contributor_count);
+    }
+
+    FundraiserEvent::AccountMigrated {
+        account: *account.key,
+        kind: kind as u8,
+        version: data[1],
+    }.emit()?;
+
+    Ok(())
+}
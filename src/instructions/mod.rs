@@ -1,28 +1,291 @@
-use solana_program::program_error::ProgramError;
+use borsh::{BorshDeserialize, BorshSerialize};
+use shank::{ShankInstruction, ShankType};
+use solana_program::{program_error::ProgramError, pubkey::Pubkey};
+
+use crate::state::{fundraiser::FundingMode, proposal::ProposalKind};
 
 pub mod initialize;
 pub mod checker;
 pub mod contribute;
 pub mod refund;
+pub mod collect;
+pub mod release_milestone;
+pub mod approve_milestone;
+pub mod abandon_milestones;
+pub mod create_proposal;
+pub mod vote;
+pub mod execute_proposal;
+pub mod claim_stream;
+pub mod migrate;
+pub mod get_status;
+pub mod init_config;
+pub mod update_config;
+pub mod set_paused;
+pub mod add_allowed_mint;
+pub mod remove_allowed_mint;
+pub mod close_contributor;
+pub mod withdraw_rent_pool;
 
-#[derive(Copy, Clone, Debug)]
+/// Instructions and their arguments, Borsh encoded: the first byte is the variant
+/// index, so new instructions must be appended. Clients build instruction data with
+/// `borsh::to_vec` on this same enum.
+///
+/// The `#[account]` attributes describe the accounts of each instruction for the IDL,
+/// keep them in the same order as the handler destructures them.
+#[derive(Clone, Debug, PartialEq, Eq, BorshDeserialize, BorshSerialize, ShankInstruction)]
 pub enum FundraiserInstructions {
-    Initialize,
-    Contribute,
-    Refund,
-    Checker,
+    #[account(0, writable, signer, name = "maker", desc = "Pays for and owns the fundraiser")]
+    #[account(1, name = "mint_to_raise", desc = "Mint of the token being raised")]
+    #[account(2, writable, name = "fundraiser", desc = "Fundraiser PDA [\"fundraiser\", maker]")]
+    #[account(3, writable, name = "vault", desc = "Vault PDA [\"vault\", fundraiser], or the fundraiser's associated token account with `ata_vault`")]
+    #[account(4, name = "system_program", desc = "System program")]
+    #[account(5, name = "token_program", desc = "Token program or Token-2022, the owner of the mint")]
+    #[account(6, name = "config", desc = "Config PDA [\"config\"]")]
+    #[account(7, name = "mint_allowlist", desc = "Mint allowlist PDA [\"mint_allowlist\"]")]
+    #[account(8, writable, name = "rent_pool", desc = "Rent pool PDA [\"rent_pool\", fundraiser], funded with `rent_pool_lamports`")]
+    #[account(9, name = "associated_token_program", desc = "Associated token account program, used with `ata_vault`")]
+    #[account(10, optional, writable, name = "milestones", desc = "Milestones PDA [\"milestones\", fundraiser], when milestone shares are set")]
+    Initialize(InitializeArgs),
+
+    /// Followed by the accounts of the mint's transfer hook, if it has one. With an
+    /// `intent`, the contributor does not sign: the ed25519 instruction before this one
+    /// carries their signature and the fundraiser transfers as delegate of their account.
+    /// With `wrap_sol`, lamports of the contributor are wrapped into a native mint vault.
+    #[account(0, writable, name = "contributor", desc = "Owner of the contributed tokens, signer unless an intent is given")]
+    #[account(1, writable, name = "contributor_account", desc = "Contributor PDA [\"contributor\", fundraiser, contributor]")]
+    #[account(2, writable, name = "contributor_ata", desc = "Contributor token account")]
+    #[account(3, writable, name = "fundraiser", desc = "Fundraiser account")]
+    #[account(4, writable, name = "vault", desc = "Fundraiser vault")]
+    #[account(5, name = "token_program", desc = "Token program")]
+    #[account(6, name = "system_program", desc = "System program")]
+    #[account(7, name = "config", desc = "Config PDA [\"config\"]")]
+    #[account(8, name = "mint_to_raise", desc = "Mint of the token being raised")]
+    #[account(9, writable, name = "rent_pool", desc = "Rent pool PDA [\"rent_pool\", fundraiser], pays for the contributor PDA while funded")]
+    #[account(10, writable, signer, name = "payer", desc = "Pays for the contributor PDA when the rent pool does not")]
+    #[account(11, name = "instructions_sysvar", desc = "Instructions sysvar, read to verify an intent")]
+    Contribute { amount: u64, intent: Option<ContributionIntentArgs>, wrap_sol: bool },
+
+    /// Followed by the accounts of the mint's transfer hook, if it has one. With
+    /// `unwrap_sol`, wrapped SOL is refunded as lamports. Optional accounts that are not
+    /// used are passed as the program ID, so the hook accounts always start at index 12.
+    #[account(0, writable, signer, name = "contributor", desc = "Contributor being refunded")]
+    #[account(1, writable, name = "contributor_account", desc = "Contributor PDA, closed on refund")]
+    #[account(2, writable, name = "contributor_ata", desc = "Contributor token account, their associated token account is created if missing. The delegation of a pledge that was never collected is revoked on it")]
+    #[account(3, writable, name = "fundraiser", desc = "Fundraiser account")]
+    #[account(4, writable, name = "vault", desc = "Fundraiser vault")]
+    #[account(5, name = "token_program", desc = "Token program")]
+    #[account(6, name = "mint_to_raise", desc = "Mint of the token being raised")]
+    #[account(7, writable, name = "rent_payer", desc = "Rent payer of the contributor PDA, the rent pool or the payer of the first contribution, gets back the rent")]
+    #[account(8, name = "system_program", desc = "System program")]
+    #[account(9, name = "associated_token_program", desc = "Associated token account program")]
+    #[account(10, optional, name = "milestones", desc = "Milestones PDA, when the fundraiser has milestones")]
+    #[account(11, optional, writable, name = "unwrap_account", desc = "Temporary wSOL account PDA [\"unwrap\", fundraiser, contributor], with `unwrap_sol`")]
+    Refund { unwrap_sol: bool },
+
+    /// Followed by the accounts of the mint's transfer hook, if it has one. With
+    /// `unwrap_sol`, wrapped SOL is paid out as lamports. Optional accounts that are not
+    /// used are passed as the program ID, so the hook accounts always start at index 12.
+    #[account(0, writable, signer, name = "maker", desc = "Fundraiser maker")]
+    #[account(1, writable, name = "maker_ata", desc = "Maker token account, their associated token account is created if missing")]
+    #[account(2, writable, name = "fundraiser", desc = "Fundraiser account, closed once paid out")]
+    #[account(3, writable, name = "vault", desc = "Fundraiser vault")]
+    #[account(4, name = "token_program", desc = "Token program")]
+    #[account(5, name = "config", desc = "Config PDA [\"config\"]")]
+    #[account(6, writable, name = "fee_account", desc = "Token account of the fee recipient, unused without a fee")]
+    #[account(7, writable, name = "mint_to_raise", desc = "Mint of the token being raised, receives the withheld transfer fees")]
+    #[account(8, name = "system_program", desc = "System program")]
+    #[account(9, name = "associated_token_program", desc = "Associated token account program")]
+    #[account(10, optional, writable, name = "milestones_or_stream", desc = "Milestones PDA, or stream PDA [\"stream\", fundraiser] when streaming")]
+    #[account(11, optional, writable, name = "unwrap_account", desc = "Temporary wSOL account PDA [\"unwrap\", fundraiser, maker], with `unwrap_sol`")]
+    Checker { unwrap_sol: bool },
+
+    #[account(0, signer, name = "maker", desc = "Fundraiser maker")]
+    #[account(1, writable, name = "fundraiser", desc = "Fundraiser account")]
+    #[account(2, writable, name = "vault", desc = "Fundraiser vault")]
+    #[account(3, name = "token_program", desc = "Token program")]
+    #[account(4, name = "mint_to_raise", desc = "Mint of the token being raised")]
+    #[account(5, name = "config", desc = "Config PDA [\"config\"]")]
+    Collect,
+
+    /// Followed by the accounts of the mint's transfer hook, if it has one
+    #[account(0, signer, name = "maker", desc = "Fundraiser maker")]
+    #[account(1, writable, name = "maker_ata", desc = "Maker token account")]
+    #[account(2, name = "fundraiser", desc = "Fundraiser account")]
+    #[account(3, writable, name = "vault", desc = "Fundraiser vault")]
+    #[account(4, writable, name = "milestones", desc = "Milestones PDA")]
+    #[account(5, name = "token_program", desc = "Token program")]
+    #[account(6, name = "mint_to_raise", desc = "Mint of the token being raised")]
+    #[account(7, name = "config", desc = "Config PDA [\"config\"]")]
+    ReleaseMilestone { index: u8 },
+
+    #[account(0, signer, name = "arbiter", desc = "Milestone arbiter")]
+    #[account(1, name = "fundraiser", desc = "Fundraiser account")]
+    #[account(2, writable, name = "milestones", desc = "Milestones PDA")]
+    ApproveMilestone { index: u8 },
+
+    #[account(0, signer, name = "authority", desc = "Milestone arbiter or fundraiser maker")]
+    #[account(1, name = "fundraiser", desc = "Fundraiser account")]
+    #[account(2, writable, name = "milestones", desc = "Milestones PDA")]
+    AbandonMilestones,
+
+    #[account(0, writable, signer, name = "proposer", desc = "Maker or contributor opening the proposal")]
+    #[account(1, writable, name = "fundraiser", desc = "Fundraiser account")]
+    #[account(2, writable, name = "proposal", desc = "Proposal PDA [\"proposal\", fundraiser, proposal_count]")]
+    #[account(3, name = "system_program", desc = "System program")]
+    #[account(4, optional, name = "contributor_account", desc = "Proposer's contributor PDA, when the proposer is not the maker")]
+    CreateProposal { kind: ProposalKind, argument: u8 },
+
+    #[account(0, writable, signer, name = "contributor", desc = "Contributor voting")]
+    #[account(1, name = "contributor_account", desc = "Contributor PDA, its amount is the vote weight")]
+    #[account(2, name = "fundraiser", desc = "Fundraiser account")]
+    #[account(3, writable, name = "proposal", desc = "Proposal being voted on")]
+    #[account(4, writable, name = "vote_record", desc = "Vote record PDA [\"vote\", proposal, contributor]")]
+    #[account(5, name = "system_program", desc = "System program")]
+    Vote { approve: bool },
+
+    #[account(0, writable, name = "fundraiser", desc = "Fundraiser account")]
+    #[account(1, writable, name = "proposal", desc = "Proposal to execute")]
+    #[account(2, optional, writable, name = "milestones", desc = "Milestones PDA, when the fundraiser has milestones")]
+    ExecuteProposal,
+
+    /// Followed by the accounts of the mint's transfer hook, if it has one
+    #[account(0, writable, signer, name = "maker", desc = "Fundraiser maker")]
+    #[account(1, writable, name = "maker_ata", desc = "Maker token account")]
+    #[account(2, writable, name = "fundraiser", desc = "Fundraiser account, closed by the final claim")]
+    #[account(3, writable, name = "vault", desc = "Fundraiser vault")]
+    #[account(4, writable, name = "stream", desc = "Stream PDA, closed by the final claim")]
+    #[account(5, name = "token_program", desc = "Token program")]
+    #[account(6, writable, name = "mint_to_raise", desc = "Mint of the token being raised, receives the withheld transfer fees")]
+    #[account(7, name = "config", desc = "Config PDA [\"config\"]")]
+    ClaimStream,
+
+    /// `kind` is the `AccountDiscriminator` of the account to migrate
+    #[account(0, writable, signer, name = "payer", desc = "Pays for the extra rent")]
+    #[account(1, writable, name = "account", desc = "Account to migrate")]
+    #[account(2, name = "system_program", desc = "System program")]
+    #[account(3, optional, writable, name = "fundraiser", desc = "Fundraiser of the contributor account being migrated, migrated before it")]
+    #[account(4, optional, name = "contributor", desc = "Owner of the contributor account being migrated")]
+    Migrate { kind: u8 },
+
+    /// Returns a `FundraiserStatus` through the return data
+    #[account(0, name = "fundraiser", desc = "Fundraiser account")]
+    GetStatus,
+
+    #[account(0, writable, signer, name = "authority", desc = "Upgrade authority of the program, pays for the config")]
+    #[account(1, writable, name = "config", desc = "Config PDA [\"config\"]")]
+    #[account(2, name = "program_data", desc = "Program data account of this program")]
+    #[account(3, name = "system_program", desc = "System program")]
+    InitConfig(ConfigArgs),
+
+    #[account(0, signer, name = "admin", desc = "Config admin")]
+    #[account(1, writable, name = "config", desc = "Config PDA [\"config\"]")]
+    UpdateConfig(ConfigArgs),
+
+    #[account(0, signer, name = "maker", desc = "Fundraiser maker")]
+    #[account(1, writable, name = "fundraiser", desc = "Fundraiser account")]
+    SetPaused { paused: bool },
+
+    #[account(0, writable, signer, name = "admin", desc = "Config admin, pays for the allowlist on first use")]
+    #[account(1, name = "config", desc = "Config PDA [\"config\"]")]
+    #[account(2, writable, name = "mint_allowlist", desc = "Mint allowlist PDA [\"mint_allowlist\"]")]
+    #[account(3, name = "system_program", desc = "System program")]
+    AddAllowedMint { mint: Pubkey },
+
+    #[account(0, signer, name = "admin", desc = "Config admin")]
+    #[account(1, name = "config", desc = "Config PDA [\"config\"]")]
+    #[account(2, writable, name = "mint_allowlist", desc = "Mint allowlist PDA [\"mint_allowlist\"]")]
+    RemoveAllowedMint { mint: Pubkey },
+
+    #[account(0, name = "contributor", desc = "Owner of the contributor account")]
+    #[account(1, writable, name = "contributor_account", desc = "Contributor PDA to close")]
+    #[account(2, writable, name = "fundraiser", desc = "Fundraiser account, closed or settled")]
+    #[account(3, writable, name = "rent_payer", desc = "Rent payer of the contributor PDA, the rent pool or the payer of the first contribution, gets back the rent")]
+    CloseContributor,
+
+    #[account(0, writable, signer, name = "maker", desc = "Fundraiser maker")]
+    #[account(1, name = "fundraiser", desc = "Fundraiser PDA [\"fundraiser\", maker], possibly closed")]
+    #[account(2, writable, name = "rent_pool", desc = "Rent pool PDA [\"rent_pool\", fundraiser]")]
+    #[account(3, name = "system_program", desc = "System program")]
+    WithdrawRentPool,
 }
 
-impl TryFrom<&u8> for FundraiserInstructions {
-    type Error = ProgramError;
+impl FundraiserInstructions {
+    /// Decodes instruction data, which must be consumed entirely.
+    pub fn unpack(instruction_data: &[u8]) -> Result<Self, ProgramError> {
+        Self::try_from_slice(instruction_data).map_err(|_| ProgramError::InvalidInstructionData)
+    }
+}
 
-    fn try_from(value: &u8) -> Result<Self, Self::Error> {
-        match value {
-            0 => Ok(FundraiserInstructions::Initialize),
-            1 => Ok(FundraiserInstructions::Contribute),
-            2 => Ok(FundraiserInstructions::Refund),
-            3 => Ok(FundraiserInstructions::Checker),
-            _ => Err(ProgramError::InvalidInstructionData)
+#[derive(Clone, Debug, PartialEq, Eq, BorshDeserialize, BorshSerialize, ShankType)]
+pub struct InitializeArgs {
+    pub amount_to_raise: u64,
+    pub time_started: i64,
+    /// Days the fundraiser accepts contributions for
+    pub duration: u8,
+    /// Contributions are only approved to the fundraiser and pulled by `Collect`
+    pub pledge_mode: bool,
+    /// Percentage released by each milestone, empty when the funds are paid out at once
+    pub milestone_shares: Vec<u8>,
+    /// Key allowed to approve milestones, `Pubkey::default()` leaves it to governance
+    pub arbiter: Pubkey,
+    /// Percentage of the raised amount that must vote on a proposal, 0 disables governance
+    pub quorum: u8,
+    /// Seconds a proposal stays open for voting
+    pub voting_period: i64,
+    /// Seconds over which the raised amount vests to the maker, 0 pays it out at once
+    pub stream_duration: i64,
+    pub funding_mode: FundingMode,
+    /// Percentage of `amount_to_raise` that must be raised for the fundraiser to succeed
+    pub min_success_percentage: u8,
+    /// Lamports the maker puts in the rent pool to pay for contributor accounts, 0 leaves
+    /// the rent to the contributors
+    pub rent_pool_lamports: u64,
+    /// Use the fundraiser's associated token account as the vault, so third-party tooling
+    /// can find it without knowing the program
+    pub ata_vault: bool,
+}
+
+impl InitializeArgs {
+    /// All-or-nothing fundraiser without any of the optional features.
+    pub fn new(amount_to_raise: u64, time_started: i64, duration: u8) -> Self {
+        Self {
+            amount_to_raise,
+            time_started,
+            duration,
+            pledge_mode: false,
+            milestone_shares: vec![],
+            arbiter: Pubkey::default(),
+            quorum: 0,
+            voting_period: 0,
+            stream_duration: 0,
+            funding_mode: FundingMode::AllOrNothing,
+            min_success_percentage: 100,
+            rent_pool_lamports: 0,
+            ata_vault: false,
         }
     }
-}
\ No newline at end of file
+}
+
+/// Contribution the donor signed off-chain, see `intent::ContributionIntent`.
+#[derive(Clone, Debug, PartialEq, Eq, BorshDeserialize, BorshSerialize, ShankType)]
+pub struct ContributionIntentArgs {
+    pub nonce: u64,
+    pub expires_at: i64,
+}
+
+/// Settings of the config account, `UpdateConfig` replaces all of them at once.
+#[derive(Clone, Debug, PartialEq, Eq, BorshDeserialize, BorshSerialize, ShankType)]
+pub struct ConfigArgs {
+    pub admin: Pubkey,
+    pub paused: bool,
+    /// Fee taken from the raised amount at payout, in basis points
+    pub fee_bps: u16,
+    /// Owner of the token accounts receiving the fees
+    pub fee_recipient: Pubkey,
+    /// Longest duration a fundraiser can run for, in days, 0 for no limit
+    pub max_duration: u8,
+    /// Largest amount a fundraiser can raise, 0 for no limit
+    pub max_amount_to_raise: u64,
+    /// Mint features (`token::EXTENSION_*` flags) that `Initialize` refuses
+    pub rejected_extensions: u16,
+}
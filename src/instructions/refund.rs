@@ -1,12 +1,12 @@
-use borsh::BorshDeserialize;
 use solana_program::{
-    account_info::AccountInfo, clock::Clock, entrypoint::ProgramResult, program::invoke_signed, program_error::ProgramError, pubkey::Pubkey, sysvar::Sysvar
+    account_info::AccountInfo, clock::Clock, entrypoint::ProgramResult, program::invoke, program_error::ProgramError, program_option::COption, pubkey::Pubkey, system_program, sysvar::Sysvar
 };
-use spl_token::instruction::transfer;
-use crate::{constants::SECONDS_TO_DAYS, error::FundraiserError, state::{contributor::Contributor, fundraiser::Fundraiser}};
+use spl_token_2022::instruction::revoke;
+use crate::{error::FundraiserError, events::FundraiserEvent, state::{contributor::{Contributor, PledgeStatus}, fundraiser::{Fundraiser, FundingMode, LifecycleStatus}, milestones::Milestones}, token::{check_destination, check_token_program, create_ata_idempotent, transfer_checked, unpack_account, unwrap_to, TransferAccounts, UnwrapAccounts}};
 
 pub fn refund(
     accounts: &[AccountInfo],
+    unwrap_sol: bool,
 ) -> ProgramResult {
 
     let [
@@ -16,12 +16,21 @@ pub fn refund(
         fundraiser,
         vault,
         token_program,
+        mint_to_raise,
+        rent_payer,
+        system_program,
+        associated_token_program,
+        milestones,
+        unwrap_account,
+        hook_accounts @ ..
     ] = accounts else {
         return Err(ProgramError::NotEnoughAccountKeys)
     };
 
     //we verify the token program id
-    if !spl_token::check_id(&token_program.key) {
+    check_token_program(token_program.key)?;
+
+    if !system_program::check_id(system_program.key) {
         return Err(ProgramError::IncorrectProgramId)
     }
 
@@ -54,55 +63,157 @@ pub fn refund(
         return Err(ProgramError::InvalidAccountData)
     }
 
-    let contributor_account_data = Contributor::try_from_slice(
+    let contributor_account_data = *Contributor::load(
         &contributor_account.try_borrow_data()?
     )?;
 
-    let mut fundraiser_account = Fundraiser::try_from_slice(
-        &fundraiser.try_borrow_mut_data()?
-    )?;
+    if fundraiser.owner != &crate::ID {
+        return Err(ProgramError::InvalidAccountOwner)
+    }
 
-    let current_time = Clock::get()?.unix_timestamp;
-    
-    // we verify that the fundraiser has ended
-    if fundraiser_account.duration <= ((current_time - fundraiser_account.time_started) / SECONDS_TO_DAYS) as u8 {
-        return Err(FundraiserError::FundraiserNotEnded.into())
+    let fundraiser_account = Fundraiser::sync(fundraiser, Clock::get()?.unix_timestamp)?;
+
+    // we check the mint is the one being raised, transfers need its decimals
+    if fundraiser_account.mint_to_raise != *mint_to_raise.key {
+        return Err(ProgramError::InvalidAccountData)
     }
 
-    // we transfer from the vault back to the contributor the amount they have in their account
-    let transfer_ix = transfer(
-        token_program.key, 
-        vault.key, 
-        contributor_ata.key, 
-        fundraiser.key, 
-        &[], 
-        contributor_account_data.amount
-    )?;
+    // we check the vault is the fundraiser's, the fundraiser signs the transfer
+    fundraiser_account.check_vault(fundraiser.key, vault, token_program.key)?;
 
-    invoke_signed(
-        &transfer_ix, 
-        &[
-            token_program.clone(),
-            vault.clone(),
-            contributor_ata.clone(),
-            fundraiser.clone(),
-        ], 
-        &[&[
-            b"contributor".as_ref(),
-            fundraiser.key.as_ref(),
-            contributor.key.as_ref(),
-            &[contributor_account_data.bump]
-        ]]
-    )?;
+    // the maker keeps whatever a flexible fundraiser raised
+    if fundraiser_account.funding_mode() == FundingMode::Flexible {
+        return Err(FundraiserError::RefundsDisabled.into())
+    }
+
+    let milestones_data = if fundraiser_account.has_milestones() {
+        Some(Milestones::load(milestones, fundraiser.key)?)
+    } else {
+        None
+    };
+
+    let refund_amount = match milestones_data {
+        // once locked into milestones, funds are only refunded pro-rata to what is
+        // left in the vault, and only after the milestones have been abandoned
+        Some(milestones_data) if milestones_data.is_funded() => {
+            if !milestones_data.abandoned {
+                return Err(FundraiserError::MilestonesNotAbandoned.into())
+            }
+
+            milestones_data.refundable_amount(contributor_account_data.amount())
+        },
+        _ => {
+            // we verify that the fundraiser failed, or that contributors voted to cancel it
+            match fundraiser_account.status()? {
+                LifecycleStatus::Failed | LifecycleStatus::Cancelled => {},
+                LifecycleStatus::Pending | LifecycleStatus::Active => {
+                    return Err(FundraiserError::FundraiserNotEnded.into())
+                },
+                LifecycleStatus::Succeeded | LifecycleStatus::Settled => {
+                    return Err(FundraiserError::FundraiserSucceeded.into())
+                },
+            }
+
+            contributor_account_data.amount()
+        }
+    };
+
+    // we transfer from the vault back to the contributor the amount they have in their account,
+    // pledges that were never collected are still sitting in the contributor's token account
+    let in_vault = !fundraiser_account.pledge_mode()
+        || contributor_account_data.pledge_status() == PledgeStatus::Collected;
+
+    let fundraiser_seeds: &[&[u8]] = &[
+        b"fundraiser".as_ref(),
+        fundraiser_account.maker.as_ref(),
+        &[fundraiser_account.bump]
+    ];
+
+    // the contributor was credited what reached the vault, a transfer fee mint withholds
+    // its fee again on the way back
+    if in_vault && unwrap_sol {
+        unwrap_to(
+            &UnwrapAccounts {
+                token_program,
+                vault,
+                mint: mint_to_raise,
+                temporary_account: unwrap_account,
+                fundraiser,
+                recipient: contributor,
+                system_program,
+            },
+            refund_amount,
+            fundraiser_seeds
+        )?;
+    } else if in_vault {
+        // the contributor pays for their token account if they closed it since
+        if contributor_ata.data_is_empty() {
+            create_ata_idempotent(
+                contributor, 
+                contributor_ata, 
+                contributor, 
+                mint_to_raise, 
+                system_program, 
+                token_program, 
+                associated_token_program
+            )?;
+        }
+
+        check_destination(contributor_ata, contributor.key, mint_to_raise.key, token_program.key)?;
+
+        transfer_checked(
+            &TransferAccounts {
+                token_program,
+                source: vault,
+                mint: mint_to_raise,
+                destination: contributor_ata,
+                authority: fundraiser,
+                hook_accounts,
+            },
+            refund_amount,
+            &[fundraiser_seeds]
+        )?;
+    } else if !contributor_ata.data_is_empty() {
+        // the pledge was never collected, the fundraiser gives up its delegation while
+        // the contributor is here to sign
+        let token_account = unpack_account(&contributor_ata.try_borrow_data()?)?;
+
+        if token_account.owner == *contributor.key && token_account.delegate == COption::Some(*fundraiser.key) {
+            invoke(
+                &revoke(token_program.key, contributor_ata.key, contributor.key, &[])?, 
+                &[token_program.clone(), contributor_ata.clone(), contributor.clone()]
+            )?;
+        }
+    }
+
+    {
+        let mut data = fundraiser.try_borrow_mut_data()?;
+        let fundraiser_account = Fundraiser::load_mut(&mut data)?;
+
+        // failed pledges were already taken out of the total by `collect`
+        if contributor_account_data.pledge_status() != PledgeStatus::Failed {
+            let current_amount = fundraiser_account.current_amount()
+                .checked_sub(contributor_account_data.amount())
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+
+            fundraiser_account.set_current_amount(current_amount);
+        }
+
+        let contributor_count = fundraiser_account.contributor_count()
+            .checked_sub(1)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+
+        fundraiser_account.set_contributor_count(contributor_count);
+    }
 
-    fundraiser_account.current_amount -= contributor_account_data.amount;
+    // we close the contributor_account, its rent goes back to whoever paid it
+    Contributor::close(contributor_account, rent_payer)?;
 
-    // we close the contributor_account
-    let balance = contributor_account.lamports();
-    contributor_account.realloc(0, false)?;
-    **contributor_account.lamports.borrow_mut() = 0;
-    **contributor.lamports.borrow_mut() += balance;
-    contributor_account.assign(&Pubkey::default());
+    FundraiserEvent::Refunded {
+        fundraiser: *fundraiser.key,
+        contributor: *contributor.key,
+        amount: refund_amount,
+    }.emit()?;
 
     Ok(())
 }
\ No newline at end of file
@@ -0,0 +1,125 @@
+use borsh::BorshSerialize;
+use solana_program::{
+    account_info::AccountInfo, 
+    entrypoint::ProgramResult, 
+    program_error::ProgramError
+};
+use crate::{
+    error::FundraiserError, 
+    events::FundraiserEvent, 
+    state::{
+        config::Config, 
+        fundraiser::Fundraiser, 
+        milestones::Milestones
+    }, 
+    token::{check_destination, check_token_program, transfer_checked, TransferAccounts}
+};
+
+pub fn release_milestone(
+    accounts: &[AccountInfo],
+    index: u8
+) -> ProgramResult {
+    let [
+        maker,
+        maker_ata,
+        fundraiser,
+        vault,
+        milestones,
+        token_program,
+        mint_to_raise,
+        config,
+        hook_accounts @ ..
+    ] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys)
+    };
+
+    let index = index as usize;
+
+    // check if the token_program had the correct id
+    check_token_program(token_program.key)?;
+
+    // we check the maker is signer
+    if !maker.is_signer {
+        return Err(ProgramError::MissingRequiredSignature)
+    }
+
+    if fundraiser.owner != &crate::ID {
+        return Err(ProgramError::InvalidAccountOwner)
+    }
+
+    let fundraiser_account = *Fundraiser::load(
+        &fundraiser.try_borrow_data()?
+    )?;
+
+    if fundraiser_account.maker != *maker.key {
+        return Err(FundraiserError::InvalidFundraiserMaker.into())
+    }
+
+    Config::load(config)?.require_unpaused(&fundraiser_account)?;
+
+    // we check the mint is the one being raised, transfers need its decimals
+    if fundraiser_account.mint_to_raise != *mint_to_raise.key {
+        return Err(ProgramError::InvalidAccountData)
+    }
+
+    // we check the vault is the fundraiser's, the fundraiser signs the transfer
+    fundraiser_account.check_vault(fundraiser.key, vault, token_program.key)?;
+
+    // we check the maker receives the payout in their own token account
+    check_destination(maker_ata, maker.key, mint_to_raise.key, token_program.key)?;
+
+    let mut milestones_data = Milestones::load(milestones, fundraiser.key)?;
+
+    if index >= milestones_data.count as usize {
+        return Err(FundraiserError::InvalidMilestones.into())
+    }
+
+    // funds are only locked into milestones once the fundraiser closed successfully
+    if !milestones_data.is_funded() {
+        return Err(FundraiserError::FundraiserNotClosed.into())
+    }
+
+    if milestones_data.abandoned {
+        return Err(FundraiserError::MilestonesAbandoned.into())
+    }
+
+    if milestones_data.milestones[index].released {
+        return Err(FundraiserError::MilestoneAlreadyReleased.into())
+    }
+
+    if !milestones_data.milestones[index].approved {
+        return Err(FundraiserError::MilestoneNotApproved.into())
+    }
+
+    let amount = milestones_data.tranche_amount(index);
+
+    transfer_checked(
+        &TransferAccounts {
+            token_program,
+            source: vault,
+            mint: mint_to_raise,
+            destination: maker_ata,
+            authority: fundraiser,
+            hook_accounts,
+        },
+        amount,
+        &[&[
+            b"fundraiser",
+            maker.key.as_ref(),
+            &[fundraiser_account.bump]
+        ]]
+    )?;
+
+    milestones_data.milestones[index].released = true;
+    milestones_data.released_amount += amount;
+
+    milestones_data.serialize(&mut *milestones.data.borrow_mut())?;
+
+    FundraiserEvent::Claimed {
+        fundraiser: *fundraiser.key,
+        maker: *maker.key,
+        amount,
+    }.emit()?;
+
+    Ok(())
+}
@@ -0,0 +1,50 @@
+use borsh::BorshSerialize;
+use solana_program::{
+    account_info::AccountInfo, 
+    entrypoint::ProgramResult, 
+    program_error::ProgramError, 
+    pubkey::Pubkey
+};
+use crate::{
+    error::FundraiserError, 
+    events::FundraiserEvent, 
+    state::{
+        config::Config, 
+        mint_allowlist::MintAllowlist
+    }
+};
+
+/// Stops new fundraisers from being created for `mint`, running ones are not affected.
+pub fn remove_allowed_mint(
+    accounts: &[AccountInfo],
+    mint: Pubkey
+) -> ProgramResult {
+    let [
+        admin,
+        config,
+        allowlist,
+    ] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys)
+    };
+
+    // we check the admin is signer
+    if !admin.is_signer {
+        return Err(ProgramError::MissingRequiredSignature)
+    }
+
+    if Config::load(config)?.admin != *admin.key {
+        return Err(FundraiserError::InvalidAdmin.into())
+    }
+
+    let mut allowlist_data = MintAllowlist::load(allowlist)?;
+
+    allowlist_data.remove(&mint)?;
+
+    allowlist_data.serialize(&mut *allowlist.data.borrow_mut())?;
+
+    FundraiserEvent::MintDisallowed {
+        mint,
+    }.emit()?;
+
+    Ok(())
+}
@@ -0,0 +1,54 @@
+use solana_program::{
+    account_info::AccountInfo, 
+    entrypoint::ProgramResult, 
+    program_error::ProgramError
+};
+use crate::{
+    error::FundraiserError, 
+    events::FundraiserEvent, 
+    state::fundraiser::Fundraiser
+};
+
+/// Lets the maker stop contributions and payouts on their fundraiser, and resume them.
+/// Refunds stay open while paused.
+pub fn set_paused(
+    accounts: &[AccountInfo],
+    paused: bool
+) -> ProgramResult {
+    let [
+        maker,
+        fundraiser,
+    ] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys)
+    };
+
+    // we check the maker is signer
+    if !maker.is_signer {
+        return Err(ProgramError::MissingRequiredSignature)
+    }
+
+    if fundraiser.owner != &crate::ID {
+        return Err(ProgramError::InvalidAccountOwner)
+    }
+
+    let mut data = fundraiser.try_borrow_mut_data()?;
+    let fundraiser_account = Fundraiser::load_mut(&mut data)?;
+
+    if fundraiser_account.maker != *maker.key {
+        return Err(FundraiserError::InvalidFundraiserMaker.into())
+    }
+
+    fundraiser_account.set_paused(paused);
+
+    if paused {
+        FundraiserEvent::FundraiserPaused {
+            fundraiser: *fundraiser.key,
+        }.emit()?;
+    } else {
+        FundraiserEvent::FundraiserUnpaused {
+            fundraiser: *fundraiser.key,
+        }.emit()?;
+    }
+
+    Ok(())
+}
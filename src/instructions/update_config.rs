@@ -0,0 +1,73 @@
+use borsh::BorshSerialize;
+use solana_program::{
+    account_info::AccountInfo, 
+    entrypoint::ProgramResult, 
+    program_error::ProgramError
+};
+use crate::{
+    constants::MAX_FEE_BPS, 
+    error::FundraiserError, 
+    events::FundraiserEvent, 
+    state::config::Config
+};
+
+use super::ConfigArgs;
+
+/// Replaces the settings of the config, handing it over when `admin` changes. Running
+/// fundraisers keep the fee they were created with.
+pub fn update_config(
+    accounts: &[AccountInfo],
+    args: ConfigArgs
+) -> ProgramResult {
+    let [
+        admin,
+        config,
+    ] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys)
+    };
+
+    // we check the admin is signer
+    if !admin.is_signer {
+        return Err(ProgramError::MissingRequiredSignature)
+    }
+
+    let mut config_data = Config::load(config)?;
+
+    if config_data.admin != *admin.key {
+        return Err(FundraiserError::InvalidAdmin.into())
+    }
+
+    if args.fee_bps > MAX_FEE_BPS {
+        return Err(ProgramError::InvalidInstructionData)
+    }
+
+    let pause_changed = config_data.paused != args.paused;
+
+    config_data.admin = args.admin;
+    config_data.paused = args.paused;
+    config_data.fee_bps = args.fee_bps;
+    config_data.fee_recipient = args.fee_recipient;
+    config_data.max_duration = args.max_duration;
+    config_data.max_amount_to_raise = args.max_amount_to_raise;
+    config_data.rejected_extensions = args.rejected_extensions;
+
+    config_data.serialize(&mut *config.data.borrow_mut())?;
+
+    FundraiserEvent::ConfigUpdated {
+        admin: config_data.admin,
+        fee_bps: config_data.fee_bps,
+        fee_recipient: config_data.fee_recipient,
+    }.emit()?;
+
+    if pause_changed && args.paused {
+        FundraiserEvent::ProgramPaused {
+            admin: *admin.key,
+        }.emit()?;
+    } else if pause_changed {
+        FundraiserEvent::ProgramUnpaused {
+            admin: *admin.key,
+        }.emit()?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,155 @@
+use borsh::BorshSerialize;
+use solana_program::{
+    account_info::AccountInfo, 
+    clock::Clock, 
+    entrypoint::ProgramResult, 
+    program::invoke_signed, 
+    program_error::ProgramError, 
+    pubkey::Pubkey, 
+    rent::Rent, 
+    system_instruction::create_account, 
+    system_program, 
+    sysvar::Sysvar
+};
+use crate::{
+    error::FundraiserError, 
+    events::FundraiserEvent, 
+    state::{
+        contributor::{Contributor, PledgeStatus}, 
+        proposal::Proposal, 
+        vote_record::VoteRecord
+    }
+};
+
+/// Votes on a proposal with a weight equal to the amount contributed, contributors who
+/// contributed since the proposal was created cannot vote on it.
+pub fn vote(
+    accounts: &[AccountInfo],
+    approve: bool
+) -> ProgramResult {
+    let [
+        contributor,
+        contributor_account,
+        fundraiser,
+        proposal,
+        vote_record,
+        system_program,
+    ] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys)
+    };
+
+    if !contributor.is_signer {
+        return Err(ProgramError::MissingRequiredSignature)
+    }
+
+    if !system_program::check_id(system_program.key) {
+        return Err(ProgramError::IncorrectProgramId)
+    }
+
+    if contributor_account.owner != &crate::ID {
+        return Err(ProgramError::InvalidAccountOwner)
+    }
+
+    // we verify the contributor_account corresponds to the contributor
+    let (contributor_pda, _) = Pubkey::find_program_address(
+        &[
+            b"contributor",
+            fundraiser.key.as_ref(),
+            contributor.key.as_ref(),
+        ], 
+        &crate::ID
+    );
+
+    if contributor_pda != *contributor_account.key {
+        return Err(ProgramError::InvalidAccountData)
+    }
+
+    let contributor_account_data = *Contributor::load(
+        &contributor_account.try_borrow_data()?
+    )?;
+
+    // failed pledges never made it into the fundraiser
+    if contributor_account_data.amount() == 0
+        || contributor_account_data.pledge_status() == PledgeStatus::Failed
+    {
+        return Err(ProgramError::InvalidAccountData)
+    }
+
+    let mut proposal_data = Proposal::load(proposal, fundraiser.key)?;
+
+    if proposal_data.executed {
+        return Err(FundraiserError::ProposalAlreadyExecuted.into())
+    }
+
+    if Clock::get()?.unix_timestamp >= proposal_data.voting_ends {
+        return Err(FundraiserError::VotingEnded.into())
+    }
+
+    // the quorum is measured against the amount raised when the proposal was created,
+    // weight added since would let a contributor buy the vote
+    if contributor_account_data.contributed_slot() >= proposal_data.created_slot {
+        return Err(FundraiserError::ContributedAfterProposal.into())
+    }
+
+    let (vote_record_pda, bump) = Pubkey::find_program_address(
+        &[
+            b"vote",
+            proposal.key.as_ref(),
+            contributor.key.as_ref(),
+        ], 
+        &crate::ID
+    );
+
+    if vote_record_pda != *vote_record.key {
+        return Err(ProgramError::InvalidSeeds)
+    }
+
+    // a contributor can only vote once per proposal
+    if !vote_record.data_is_empty() {
+        return Err(ProgramError::AccountAlreadyInitialized)
+    }
+
+    let create_vote_record_ix = create_account(
+        contributor.key, 
+        vote_record.key, 
+        Rent::get()?.minimum_balance(VoteRecord::LEN), 
+        VoteRecord::LEN as u64, 
+        &crate::ID
+    );
+
+    invoke_signed(
+        &create_vote_record_ix, 
+        &[
+            contributor.clone(),
+            vote_record.clone(),
+            system_program.clone(),
+        ], 
+        &[&[
+            b"vote",
+            proposal.key.as_ref(),
+            contributor.key.as_ref(),
+            &[bump]
+        ]]
+    )?;
+
+    let weight = contributor_account_data.amount();
+
+    VoteRecord::init(vote_record, approve, weight, bump)?;
+
+    if approve {
+        proposal_data.votes_for += weight;
+    } else {
+        proposal_data.votes_against += weight;
+    }
+
+    proposal_data.serialize(&mut *proposal.data.borrow_mut())?;
+
+    FundraiserEvent::Voted {
+        proposal: *proposal.key,
+        contributor: *contributor.key,
+        approve,
+        weight,
+    }.emit()?;
+
+    Ok(())
+}
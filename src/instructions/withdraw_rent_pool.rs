@@ -0,0 +1,71 @@
+use solana_program::{
+    account_info::AccountInfo, 
+    entrypoint::ProgramResult, 
+    program::invoke_signed, 
+    program_error::ProgramError, 
+    pubkey::Pubkey, 
+    system_instruction::transfer, 
+    system_program
+};
+use crate::{events::FundraiserEvent, state::rent_pool::RentPool};
+
+/// Sends what is left in the rent pool back to the maker. New contributors pay their own
+/// rent afterwards, and sponsored accounts closed later refill the pool.
+pub fn withdraw_rent_pool(
+    accounts: &[AccountInfo]
+) -> ProgramResult {
+    let [
+        maker,
+        fundraiser,
+        rent_pool,
+        system_program,
+    ] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys)
+    };
+
+    // we check the maker is signer
+    if !maker.is_signer {
+        return Err(ProgramError::MissingRequiredSignature)
+    }
+
+    // we check the id of system_program
+    if !system_program::check_id(system_program.key) {
+        return Err(ProgramError::IncorrectProgramId)
+    }
+
+    // the fundraiser may already be closed, so the maker is checked through its address
+    let (fundraiser_pda, _) = Pubkey::find_program_address(
+        &[b"fundraiser", maker.key.as_ref()], 
+        &crate::ID
+    );
+
+    if fundraiser_pda != *fundraiser.key {
+        return Err(ProgramError::InvalidSeeds)
+    }
+
+    let rent_pool_bump = RentPool::check(rent_pool, fundraiser.key)?;
+
+    let amount = rent_pool.lamports();
+
+    let transfer_ix = transfer(
+        rent_pool.key, 
+        maker.key, 
+        amount
+    );
+
+    invoke_signed(
+        &transfer_ix, 
+        &[
+            rent_pool.clone(),
+            maker.clone(),
+            system_program.clone(),
+        ], 
+        &[&[b"rent_pool", fundraiser.key.as_ref(), &[rent_pool_bump]]]
+    )?;
+
+    FundraiserEvent::RentPoolWithdrawn {
+        fundraiser: *fundraiser.key,
+        maker: *maker.key,
+        amount,
+    }.emit()
+}
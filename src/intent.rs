@@ -0,0 +1,97 @@
+//! Contributions authorized off-chain. The donor signs a `ContributionIntent` with their
+//! wallet and approves the fundraiser as delegate of their token account, then anyone can
+//! submit the contribution with an ed25519 program instruction carrying the signature
+//! placed right before `Contribute`.
+//!
+//! The nonce of the last intent is kept on the contributor account, which `refund` and
+//! `close_contributor` close, resetting it. That only happens once the fundraiser stopped
+//! taking contributions for good, so the intent cannot be replayed on it. Two more checks
+//! keep a replay from working anywhere else:
+//!
+//! - intents have to expire by the fundraiser's deadline, so none is still valid once a
+//!   fundraiser that ran its course is replaced at the same address
+//! - the contributor has to approve the fundraiser for exactly the intent amount, which the
+//!   contribution uses up, so a fundraiser re-created at the same address before that
+//!   still needs a new approval from the contributor
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    account_info::AccountInfo,
+    ed25519_program,
+    entrypoint::ProgramResult,
+    pubkey::Pubkey,
+    sysvar::instructions::{load_current_index_checked, load_instruction_at_checked}
+};
+
+use crate::error::FundraiserError;
+
+/// Signature, public key and message offsets of a single ed25519 signature, followed by
+/// the two bytes of the signature count and padding
+const SIGNATURE_OFFSETS_START: usize = 2;
+const SIGNATURE_OFFSETS_LEN: usize = 14;
+
+/// Message the donor signs, Borsh encoded.
+#[derive(Clone, Debug, PartialEq, Eq, BorshDeserialize, BorshSerialize)]
+pub struct ContributionIntent {
+    pub program_id: Pubkey,
+    pub fundraiser: Pubkey,
+    pub contributor: Pubkey,
+    pub amount: u64,
+    /// Must be greater than the nonce of the donor's last intent on this fundraiser
+    pub nonce: u64,
+    /// Unix timestamp after which the intent can no longer be submitted, at the latest the
+    /// fundraiser's deadline
+    pub expires_at: i64,
+}
+
+impl ContributionIntent {
+    pub fn message(&self) -> Vec<u8> {
+        borsh::to_vec(self).unwrap()
+    }
+
+    /// Checks the instruction before the current one verifies the signature of
+    /// `contributor` over this intent.
+    pub fn verify(
+        &self,
+        instructions_sysvar: &AccountInfo
+    ) -> ProgramResult {
+        // `load_current_index_checked` rejects any account other than the sysvar
+        let current_index = load_current_index_checked(instructions_sysvar)?;
+
+        let Some(index) = current_index.checked_sub(1) else {
+            return Err(FundraiserError::InvalidIntent.into())
+        };
+
+        let ed25519_ix = load_instruction_at_checked(index as usize, instructions_sysvar)?;
+
+        if !ed25519_program::check_id(&ed25519_ix.program_id) {
+            return Err(FundraiserError::InvalidIntent.into())
+        }
+
+        let data = ed25519_ix.data.as_slice();
+
+        // a single signature, whose parts all live in the ed25519 instruction itself
+        if data.len() < SIGNATURE_OFFSETS_START + SIGNATURE_OFFSETS_LEN || data[0] != 1 {
+            return Err(FundraiserError::InvalidIntent.into())
+        }
+
+        let offsets = &data[SIGNATURE_OFFSETS_START..SIGNATURE_OFFSETS_START + SIGNATURE_OFFSETS_LEN];
+        let read = |i: usize| u16::from_le_bytes([offsets[i * 2], offsets[i * 2 + 1]]);
+
+        let [_, signature_ix, public_key_offset, public_key_ix, message_offset, message_size, message_ix] =
+            core::array::from_fn(read);
+
+        if [signature_ix, public_key_ix, message_ix].iter().any(|ix| *ix != u16::MAX) {
+            return Err(FundraiserError::InvalidIntent.into())
+        }
+
+        let public_key = data.get(public_key_offset as usize..public_key_offset as usize + 32);
+        let message = data.get(message_offset as usize..message_offset as usize + message_size as usize);
+
+        if public_key != Some(self.contributor.as_ref()) || message != Some(self.message().as_slice()) {
+            return Err(FundraiserError::InvalidIntent.into())
+        }
+
+        Ok(())
+    }
+}
@@ -1,14 +1,30 @@
-mod state;
-mod instructions;
+//! Native fundraiser program.
+//!
+//! The IDL is generated with [shank](https://github.com/metaplex-foundation/shank) from the
+//! annotated `FundraiserInstructions`, the state accounts and `FundraiserError`:
+//!
+//! ```sh
+//! shank idl -r . -o idl -p 22222222222222222222222222222222222222222222
+//! ```
+//!
+//! The output uses the Anchor IDL layout, so it can be fed to Codama
+//! (`rootNodeFromAnchor`) to render the TypeScript and Python clients. Regenerate it
+//! whenever an instruction, account or error changes.
+
+pub mod state;
+pub mod instructions;
 mod constants;
 mod error;
+pub mod events;
+pub mod token;
+pub mod intent;
+
+#[cfg(test)]
 mod tests;
 
 use solana_program::{
     account_info::AccountInfo, 
     entrypoint::ProgramResult, 
-    entrypoint,
-    program_error::ProgramError, 
     pubkey::Pubkey,
     pubkey
 };
@@ -19,26 +35,57 @@ use instructions::{
     contribute::contribute,
     refund::refund,
     checker::checker,
+    collect::collect,
+    release_milestone::release_milestone,
+    approve_milestone::approve_milestone,
+    abandon_milestones::abandon_milestones,
+    create_proposal::create_proposal,
+    vote::vote,
+    execute_proposal::execute_proposal,
+    claim_stream::claim_stream,
+    migrate::migrate,
+    get_status::get_status,
+    init_config::init_config,
+    update_config::update_config,
+    set_paused::set_paused,
+    add_allowed_mint::add_allowed_mint,
+    remove_allowed_mint::remove_allowed_mint,
+    close_contributor::close_contributor,
+    withdraw_rent_pool::withdraw_rent_pool,
 };
 
 pub const ID: Pubkey =
     pubkey!("22222222222222222222222222222222222222222222");
 
-entrypoint!(process_instruction);
+#[cfg(not(feature = "no-entrypoint"))]
+solana_program::entrypoint!(process_instruction);
 
-fn process_instruction(
+pub fn process_instruction(
     _program_id: &Pubkey,
     accounts: &[AccountInfo],
     instruction_data: &[u8]
 ) -> ProgramResult {
-    let (discriminator, instruction_data) = instruction_data
-        .split_first()
-        .ok_or(ProgramError::InvalidInstructionData)?;
-
-    match FundraiserInstructions::try_from(discriminator)? {
-        FundraiserInstructions::Initialize => initialize(accounts, instruction_data),
-        FundraiserInstructions::Contribute => contribute(accounts, instruction_data),
-        FundraiserInstructions::Refund => refund(accounts),
-        FundraiserInstructions::Checker => checker(accounts),
+    match FundraiserInstructions::unpack(instruction_data)? {
+        FundraiserInstructions::Initialize(args) => initialize(accounts, args),
+        FundraiserInstructions::Contribute { amount, intent, wrap_sol } => contribute(accounts, amount, intent, wrap_sol),
+        FundraiserInstructions::Refund { unwrap_sol } => refund(accounts, unwrap_sol),
+        FundraiserInstructions::Checker { unwrap_sol } => checker(accounts, unwrap_sol),
+        FundraiserInstructions::Collect => collect(accounts),
+        FundraiserInstructions::ReleaseMilestone { index } => release_milestone(accounts, index),
+        FundraiserInstructions::ApproveMilestone { index } => approve_milestone(accounts, index),
+        FundraiserInstructions::AbandonMilestones => abandon_milestones(accounts),
+        FundraiserInstructions::CreateProposal { kind, argument } => create_proposal(accounts, kind, argument),
+        FundraiserInstructions::Vote { approve } => vote(accounts, approve),
+        FundraiserInstructions::ExecuteProposal => execute_proposal(accounts),
+        FundraiserInstructions::ClaimStream => claim_stream(accounts),
+        FundraiserInstructions::Migrate { kind } => migrate(accounts, kind),
+        FundraiserInstructions::GetStatus => get_status(accounts),
+        FundraiserInstructions::InitConfig(args) => init_config(accounts, args),
+        FundraiserInstructions::UpdateConfig(args) => update_config(accounts, args),
+        FundraiserInstructions::SetPaused { paused } => set_paused(accounts, paused),
+        FundraiserInstructions::AddAllowedMint { mint } => add_allowed_mint(accounts, mint),
+        FundraiserInstructions::RemoveAllowedMint { mint } => remove_allowed_mint(accounts, mint),
+        FundraiserInstructions::CloseContributor => close_contributor(accounts),
+        FundraiserInstructions::WithdrawRentPool => withdraw_rent_pool(accounts),
     }
 }
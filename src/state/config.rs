@@ -0,0 +1,104 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use shank::ShankAccount;
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, program_error::ProgramError, pubkey::Pubkey
+};
+
+use crate::{constants::MAX_FEE_BPS, error::FundraiserError, instructions::ConfigArgs};
+
+use super::{check_header, fundraiser::Fundraiser, AccountDiscriminator, ACCOUNT_HEADER_LEN};
+
+/// Program-wide settings, a single PDA at `[b"config"]` managed by `admin`.
+#[derive(Debug, Clone, Copy, BorshDeserialize, BorshSerialize, ShankAccount)]
+pub struct Config {
+    pub discriminator: u8,
+    pub version: u8,
+    /// Key allowed to update the config
+    pub admin: Pubkey,
+    /// Stops new fundraisers and contributions
+    pub paused: bool,
+    /// Fee taken from the raised amount at payout, in basis points
+    pub fee_bps: u16,
+    /// Owner of the token accounts receiving the fees
+    pub fee_recipient: Pubkey,
+    /// Longest duration a fundraiser can run for, in days, 0 for no limit
+    pub max_duration: u8,
+    /// Largest amount a fundraiser can raise, 0 for no limit
+    pub max_amount_to_raise: u64,
+    pub bump: u8,
+    /// Mint features (`token::EXTENSION_*` flags) `initialize` refuses, the others are
+    /// only flagged on the fundraiser
+    pub rejected_extensions: u16,
+}
+
+impl Config {
+    pub const LEN: usize = ACCOUNT_HEADER_LEN + 32 + 1 + 2 + 32 + 1 + 8 + 1 + 2;
+    pub const VERSION: u8 = 1;
+
+    #[inline]
+    pub fn init(
+        config: &AccountInfo,
+        args: &ConfigArgs,
+        bump: u8
+    ) -> ProgramResult {
+        let config_data = Config {
+            discriminator: AccountDiscriminator::Config as u8,
+            version: Self::VERSION,
+            admin: args.admin,
+            paused: args.paused,
+            fee_bps: args.fee_bps,
+            fee_recipient: args.fee_recipient,
+            max_duration: args.max_duration,
+            max_amount_to_raise: args.max_amount_to_raise,
+            bump,
+            rejected_extensions: args.rejected_extensions,
+        };
+
+        config_data.serialize(&mut *config.data.borrow_mut())?;
+
+        Ok(())
+    }
+
+    /// Deserializes the account, checking it is the config PDA.
+    #[inline]
+    pub fn load(
+        config: &AccountInfo
+    ) -> Result<Self, ProgramError> {
+        if config.owner != &crate::ID {
+            return Err(ProgramError::InvalidAccountOwner)
+        }
+
+        let data = config.try_borrow_data()?;
+
+        check_header(&data, AccountDiscriminator::Config, Self::VERSION)?;
+
+        let config_data = Self::try_from_slice(&data)?;
+
+        let expected_pda = Pubkey::create_program_address(
+            &[b"config", &[config_data.bump]],
+            &crate::ID
+        )?;
+
+        if expected_pda != *config.key {
+            return Err(ProgramError::InvalidSeeds)
+        }
+
+        Ok(config_data)
+    }
+
+    /// Contributions and payouts stop while the program or the fundraiser is paused.
+    #[inline]
+    pub fn require_unpaused(&self, fundraiser: &Fundraiser) -> ProgramResult {
+        if self.paused || fundraiser.paused() {
+            return Err(FundraiserError::Paused.into())
+        }
+
+        Ok(())
+    }
+
+    /// Fee owed on `amount` raised.
+    #[inline]
+    pub fn fee(fee_bps: u16, amount: u64) -> u64 {
+        (amount as u128 * fee_bps as u128 / MAX_FEE_BPS as u128) as u64
+    }
+}
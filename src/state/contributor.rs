@@ -1,27 +1,126 @@
 use borsh::{BorshDeserialize, BorshSerialize};
-use solana_program::{account_info::AccountInfo, entrypoint::ProgramResult};
+use shank::{ShankAccount, ShankType};
+use solana_program::{account_info::AccountInfo, entrypoint::ProgramResult, program_error::ProgramError, pubkey::Pubkey};
 
-#[derive(Debug, Clone, Copy, BorshDeserialize, BorshSerialize)]
+use crate::error::FundraiserError;
+
+use super::{check_header, AccountDiscriminator};
+
+/// Lifecycle of a pledge made against a fundraiser running in pledge mode.
+/// Contributions to regular fundraisers stay `Pending` as they are already in the vault.
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, BorshDeserialize, BorshSerialize, ShankType)]
+pub enum PledgeStatus {
+    Pending,
+    Collected,
+    Failed,
+}
+
+/// Zero-copy view over the contributor account, see `Fundraiser` for the layout rules.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, BorshDeserialize, BorshSerialize, ShankAccount)]
 pub struct Contributor {
-    pub amount: u64,
+    discriminator: u8,
+    version: u8,
+    #[idl_type("u64")]
+    amount: [u8; 8],
     pub bump: u8,
+    #[idl_type("PledgeStatus")]
+    pledge_status: u8,
+    /// Rent paid by the fundraiser's rent pool rather than the contributor
+    #[idl_type("bool")]
+    rent_sponsored: u8,
+    /// Nonce of the last contribution intent submitted for the contributor
+    #[idl_type("u64")]
+    intent_nonce: [u8; 8],
+    /// Slot of the last contribution, only contributions made before a proposal was
+    /// created can vote on it
+    #[idl_type("u64")]
+    contributed_slot: [u8; 8],
+    /// Account that paid the rent, the rent pool or the payer of the first contribution
+    pub rent_payer: Pubkey,
 }
 
+const _: () = assert!(core::mem::align_of::<Contributor>() == 1);
+
 impl Contributor {
-    pub const LEN: usize = 9;
+    pub const LEN: usize = core::mem::size_of::<Contributor>();
+    pub const VERSION: u8 = 1;
+    /// Length of the contributors created before headers: `amount` and `bump`, Borsh encoded.
+    pub const V0_LEN: usize = 9;
+
+    #[inline]
+    pub fn load(data: &[u8]) -> Result<&Self, ProgramError> {
+        check_header(data, AccountDiscriminator::Contributor, Self::VERSION)?;
+
+        if data.len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData)
+        }
+
+        // SAFETY: the length is checked above and `Contributor` has an alignment of 1
+        Ok(unsafe { &*(data.as_ptr() as *const Self) })
+    }
+
+    #[inline]
+    pub fn load_mut(data: &mut [u8]) -> Result<&mut Self, ProgramError> {
+        check_header(data, AccountDiscriminator::Contributor, Self::VERSION)?;
+
+        if data.len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData)
+        }
+
+        // SAFETY: the length is checked above and `Contributor` has an alignment of 1
+        Ok(unsafe { &mut *(data.as_mut_ptr() as *mut Self) })
+    }
+
+    /// Writes the header into freshly allocated account data and returns a view over it.
+    #[inline]
+    pub fn new_mut(data: &mut [u8]) -> Result<&mut Self, ProgramError> {
+        if data.len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData)
+        }
+
+        data[0] = AccountDiscriminator::Contributor as u8;
+        data[1] = Self::VERSION;
+
+        Self::load_mut(data)
+    }
 
     #[inline]
     pub fn init(
         contributor_pda: &AccountInfo,
         amount: u64,
-        bump: u8
+        bump: u8,
+        rent_sponsored: bool,
+        rent_payer: &Pubkey
     ) -> ProgramResult {
-        let mut contributor_account = Self::try_from_slice(&contributor_pda.try_borrow_mut_data()?)?;
+        let mut data = contributor_pda.try_borrow_mut_data()?;
+        let contributor_account = Self::new_mut(&mut data)?;
 
-        contributor_account.amount = amount;
+        contributor_account.set_amount(amount);
         contributor_account.bump = bump;
-        
-        contributor_account.serialize(&mut *contributor_pda.data.borrow_mut())?;
+        contributor_account.set_pledge_status(PledgeStatus::Pending);
+        contributor_account.rent_sponsored = rent_sponsored as u8;
+        contributor_account.rent_payer = *rent_payer;
+
+        Ok(())
+    }
+
+    /// Closes the account, handing its rent back to `rent_payer`, which has to be the
+    /// account that paid it.
+    pub fn close<'a>(
+        contributor_pda: &AccountInfo<'a>,
+        rent_payer: &AccountInfo<'a>
+    ) -> ProgramResult {
+        if Self::load(&contributor_pda.try_borrow_data()?)?.rent_payer != *rent_payer.key {
+            return Err(FundraiserError::InvalidRentPayer.into())
+        }
+
+        let balance = contributor_pda.lamports();
+        contributor_pda.realloc(0, false)?;
+        **contributor_pda.lamports.borrow_mut() = 0;
+        **rent_payer.lamports.borrow_mut() += balance;
+        contributor_pda.assign(&Pubkey::default());
 
         Ok(())
     }
@@ -30,15 +129,64 @@ impl Contributor {
         contributor_pda: &AccountInfo,
         amount_to_increase: u64
     ) -> ProgramResult {
+        let mut data = contributor_pda.try_borrow_mut_data()?;
+        let contributor_account = Self::load_mut(&mut data)?;
 
-        let mut contributor_account = Self::try_from_slice(&contributor_pda.try_borrow_mut_data()?)?;
-
-        contributor_account.amount += amount_to_increase;
+        let amount = contributor_account.amount()
+            .checked_add(amount_to_increase)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
 
-        contributor_account.serialize(&mut *contributor_pda.data.borrow_mut())?;
+        contributor_account.set_amount(amount);
 
         Ok(())
     }
 
+    #[inline]
+    pub fn amount(&self) -> u64 {
+        u64::from_le_bytes(self.amount)
+    }
+
+    #[inline]
+    pub fn set_amount(&mut self, amount: u64) {
+        self.amount = amount.to_le_bytes();
+    }
+
+    #[inline]
+    pub fn pledge_status(&self) -> PledgeStatus {
+        match self.pledge_status {
+            1 => PledgeStatus::Collected,
+            2 => PledgeStatus::Failed,
+            _ => PledgeStatus::Pending,
+        }
+    }
+
+    #[inline]
+    pub fn set_pledge_status(&mut self, pledge_status: PledgeStatus) {
+        self.pledge_status = pledge_status as u8;
+    }
+
+    #[inline]
+    pub fn rent_sponsored(&self) -> bool {
+        self.rent_sponsored != 0
+    }
+
+    #[inline]
+    pub fn intent_nonce(&self) -> u64 {
+        u64::from_le_bytes(self.intent_nonce)
+    }
+
+    #[inline]
+    pub fn set_intent_nonce(&mut self, intent_nonce: u64) {
+        self.intent_nonce = intent_nonce.to_le_bytes();
+    }
 
-}
\ No newline at end of file
+    #[inline]
+    pub fn contributed_slot(&self) -> u64 {
+        u64::from_le_bytes(self.contributed_slot)
+    }
+
+    #[inline]
+    pub fn set_contributed_slot(&mut self, contributed_slot: u64) {
+        self.contributed_slot = contributed_slot.to_le_bytes();
+    }
+}
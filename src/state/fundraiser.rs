@@ -1,69 +1,527 @@
 
 use solana_program::{
-    account_info::AccountInfo, entrypoint::ProgramResult, pubkey::Pubkey
+    account_info::AccountInfo, entrypoint::ProgramResult, program_error::ProgramError, pubkey::Pubkey
 };
 use borsh::{BorshDeserialize, BorshSerialize};
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+use shank::{ShankAccount, ShankType};
 
-use crate::{constants::SECONDS_TO_DAYS, error::FundraiserError};
+use crate::{constants::SECONDS_TO_DAYS, error::FundraiserError, instructions::InitializeArgs};
 
-#[derive(Debug, Clone, Copy, BorshDeserialize, BorshSerialize)]
+use super::{check_header, AccountDiscriminator};
+
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, BorshDeserialize, BorshSerialize, ShankType)]
+pub enum FundingMode {
+    /// The maker only gets the funds if `amount_to_raise` is met, otherwise contributors refund
+    AllOrNothing,
+    /// The maker keeps whatever is raised and can withdraw it at any time, refunds are disabled
+    Flexible,
+}
+
+/// Lifecycle of a fundraiser. Every change goes through `Fundraiser::transition`, which
+/// only allows the moves listed in `can_transition_to`.
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, BorshDeserialize, BorshSerialize, ShankType)]
+pub enum LifecycleStatus {
+    /// Created with a `time_started` in the future
+    Pending,
+    /// Accepting contributions until the deadline
+    Active,
+    /// Reached the success threshold, the maker can be paid out once pledges are collected
+    Succeeded,
+    /// Ended below the success threshold, contributors can refund
+    Failed,
+    /// Cancelled by its contributors, contributors can refund
+    Cancelled,
+    /// Paid out to the maker, directly or into milestones or a stream
+    Settled,
+}
+
+impl LifecycleStatus {
+    pub const ALL: [LifecycleStatus; 6] = [
+        LifecycleStatus::Pending,
+        LifecycleStatus::Active,
+        LifecycleStatus::Succeeded,
+        LifecycleStatus::Failed,
+        LifecycleStatus::Cancelled,
+        LifecycleStatus::Settled,
+    ];
+
+    pub const fn can_transition_to(self, to: LifecycleStatus) -> bool {
+        use LifecycleStatus::*;
+
+        matches!(
+            (self, to),
+            (Pending, Active)
+                | (Pending, Cancelled)
+                | (Active, Succeeded)
+                | (Active, Failed)
+                | (Active, Cancelled)
+                | (Succeeded, Settled)
+                // pledges that fail to be collected can take the total back below the threshold
+                | (Succeeded, Failed)
+                | (Succeeded, Cancelled)
+                // funds locked into milestones can still be handed back by governance
+                | (Settled, Cancelled)
+        )
+    }
+}
+
+impl TryFrom<u8> for LifecycleStatus {
+    type Error = ProgramError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        Self::ALL.get(value as usize).copied().ok_or(ProgramError::InvalidAccountData)
+    }
+}
+
+/// Phase of a fundraiser, as reported by `GetStatus`.
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, BorshDeserialize, BorshSerialize, ShankType)]
+pub enum FundraiserPhase {
+    /// Still accepting contributions
+    Active,
+    /// Ended with enough raised, or flexible and ended
+    Succeeded,
+    /// Ended below the success threshold, contributors can refund
+    Failed,
+    /// Cancelled by its contributors, contributors can refund
+    Cancelled,
+}
+
+/// Zero-copy view over the fundraiser account.
+///
+/// Every field has an alignment of 1, so the struct can be cast straight from the
+/// account data. Multi-byte integers are stored little-endian and accessed through
+/// getters and setters, which keeps the layout identical to the Borsh encoding clients
+/// deserialize. `#[idl_type]` gives the IDL the type behind each byte array.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, BorshDeserialize, BorshSerialize, ShankAccount)]
 pub struct Fundraiser {
+    discriminator: u8,
+    version: u8,
     pub maker: Pubkey,
     pub mint_to_raise: Pubkey,
-    pub amount_to_raise: u64,
-    pub current_amount: u64,
-    pub time_started: i64,
+    #[idl_type("u64")]
+    amount_to_raise: [u8; 8],
+    #[idl_type("u64")]
+    current_amount: [u8; 8],
+    #[idl_type("i64")]
+    time_started: [u8; 8],
     pub duration: u8,
     pub bump: u8,
+    #[idl_type("bool")]
+    pledge_mode: u8,
+    #[idl_type("bool")]
+    has_milestones: u8,
+    /// Percentage of `current_amount` that must vote for a proposal to pass, 0 disables governance
+    pub quorum: u8,
+    /// Seconds a proposal stays open for voting
+    #[idl_type("i64")]
+    voting_period: [u8; 8],
+    #[idl_type("u64")]
+    proposal_count: [u8; 8],
+    #[idl_type("LifecycleStatus")]
+    status: u8,
+    /// Seconds over which the raised amount vests to the maker, 0 pays it out at once
+    #[idl_type("i64")]
+    stream_duration: [u8; 8],
+    #[idl_type("FundingMode")]
+    funding_mode: u8,
+    /// Percentage of `amount_to_raise` that must be raised for the fundraiser to succeed
+    pub min_success_percentage: u8,
+    /// Open contributor accounts
+    #[idl_type("u64")]
+    contributor_count: [u8; 8],
+    /// Fee in basis points taken at payout, copied from the config at creation
+    #[idl_type("u16")]
+    fee_bps: [u8; 2],
+    /// Set by the maker to stop contributions and payouts
+    #[idl_type("bool")]
+    paused: u8,
+    /// Features of the mint that can drain or lock the vault, as `token::EXTENSION_*` flags
+    #[idl_type("u16")]
+    mint_extensions: [u8; 2],
+    /// The vault is the fundraiser's associated token account rather than the
+    /// `[b"vault", fundraiser]` PDA
+    #[idl_type("bool")]
+    ata_vault: u8,
+    /// Pledges not collected or failed yet, the payout waits for them
+    #[idl_type("u64")]
+    pending_pledges: [u8; 8],
 }
 
+const _: () = assert!(core::mem::align_of::<Fundraiser>() == 1);
+
 impl Fundraiser {
-    pub const LEN: usize = 96;
+    pub const LEN: usize = core::mem::size_of::<Fundraiser>();
+    pub const VERSION: u8 = 1;
+    /// Length of the fundraisers created before headers: `maker`, `mint_to_raise`,
+    /// `amount_to_raise`, `current_amount`, `time_started`, `duration` and `bump`, Borsh
+    /// encoded in that order and followed by 6 unused bytes.
+    pub const V0_LEN: usize = 96;
 
+    #[inline]
+    pub fn load(data: &[u8]) -> Result<&Self, ProgramError> {
+        check_header(data, AccountDiscriminator::Fundraiser, Self::VERSION)?;
+
+        if data.len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData)
+        }
+
+        // SAFETY: the length is checked above and `Fundraiser` has an alignment of 1
+        Ok(unsafe { &*(data.as_ptr() as *const Self) })
+    }
+
+    #[inline]
+    pub fn load_mut(data: &mut [u8]) -> Result<&mut Self, ProgramError> {
+        check_header(data, AccountDiscriminator::Fundraiser, Self::VERSION)?;
+
+        if data.len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData)
+        }
+
+        // SAFETY: the length is checked above and `Fundraiser` has an alignment of 1
+        Ok(unsafe { &mut *(data.as_mut_ptr() as *mut Self) })
+    }
+
+    /// Writes the header into freshly allocated account data and returns a view over it.
+    #[inline]
+    pub fn new_mut(data: &mut [u8]) -> Result<&mut Self, ProgramError> {
+        if data.len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData)
+        }
+
+        data[0] = AccountDiscriminator::Fundraiser as u8;
+        data[1] = Self::VERSION;
+
+        Self::load_mut(data)
+    }
+
+    /// Writes a new fundraiser set up from `args`, `fee_bps` and `mint_extensions` come
+    /// from the config and the mint.
     #[inline]
     pub fn init(
         fundraiser: &AccountInfo,
         maker: &Pubkey,
         mint_to_raise: &Pubkey,
-        amount_to_raise: u64,
-        current_amount: u64,
-        time_started: i64,
-        duration: u8,
-        bump: u8
+        args: &InitializeArgs,
+        bump: u8,
+        fee_bps: u16,
+        mint_extensions: u16,
     ) -> ProgramResult {
-        
-        let fundraiser_data = Fundraiser {
-            maker: *maker,
-            mint_to_raise: *mint_to_raise,
-            amount_to_raise,
-            current_amount,
-            time_started,
-            duration,
-            bump
-        };
-        
-        // Serialize the new instance directly into the account data
-        fundraiser_data.serialize(&mut *fundraiser.data.borrow_mut())?;
+        let mut data = fundraiser.try_borrow_mut_data()?;
+        let fundraiser_account = Self::new_mut(&mut data)?;
+
+        fundraiser_account.maker = *maker;
+        fundraiser_account.mint_to_raise = *mint_to_raise;
+        fundraiser_account.set_amount_to_raise(args.amount_to_raise);
+        fundraiser_account.set_current_amount(0);
+        fundraiser_account.set_time_started(args.time_started);
+        fundraiser_account.duration = args.duration;
+        fundraiser_account.bump = bump;
+        fundraiser_account.pledge_mode = args.pledge_mode as u8;
+        fundraiser_account.has_milestones = !args.milestone_shares.is_empty() as u8;
+        fundraiser_account.quorum = args.quorum;
+        fundraiser_account.voting_period = args.voting_period.to_le_bytes();
+        fundraiser_account.set_proposal_count(0);
+        fundraiser_account.status = LifecycleStatus::Pending as u8;
+        fundraiser_account.stream_duration = args.stream_duration.to_le_bytes();
+        fundraiser_account.funding_mode = args.funding_mode as u8;
+        fundraiser_account.min_success_percentage = args.min_success_percentage;
+        fundraiser_account.set_contributor_count(0);
+        fundraiser_account.fee_bps = fee_bps.to_le_bytes();
+        fundraiser_account.set_paused(false);
+        fundraiser_account.mint_extensions = mint_extensions.to_le_bytes();
+        fundraiser_account.ata_vault = args.ata_vault as u8;
+        fundraiser_account.set_pending_pledges(0);
 
         Ok(())
     }
 
+    /// Address of the vault: the fundraiser's associated token account with `ata_vault`,
+    /// which wallets and explorers find on their own, the `[b"vault", fundraiser]` PDA otherwise.
+    pub fn vault_address(
+        fundraiser: &Pubkey,
+        mint_to_raise: &Pubkey,
+        token_program: &Pubkey,
+        ata_vault: bool
+    ) -> Pubkey {
+        if ata_vault {
+            get_associated_token_address_with_program_id(fundraiser, mint_to_raise, token_program)
+        } else {
+            Pubkey::find_program_address(&[b"vault", fundraiser.as_ref()], &crate::ID).0
+        }
+    }
+
+    /// Checks `vault` is the vault of the fundraiser. The fundraiser PDA can be delegate of
+    /// contributors' token accounts, so every transfer it signs has to go through the vault.
     #[inline]
-    pub fn increase_amount(
+    pub fn check_vault(
+        &self,
+        fundraiser: &Pubkey,
+        vault: &AccountInfo,
+        token_program: &Pubkey
+    ) -> ProgramResult {
+        if Self::vault_address(fundraiser, &self.mint_to_raise, token_program, self.ata_vault()) != *vault.key {
+            return Err(ProgramError::InvalidSeeds)
+        }
+
+        Ok(())
+    }
+
+    /// Applies the transitions driven by the clock to the fundraiser account and returns a
+    /// copy of it, handlers call this before looking at the status.
+    #[inline]
+    pub fn sync(
         fundraiser: &AccountInfo,
-        amount_to_increase: u64,
         current_time: i64
+    ) -> Result<Self, ProgramError> {
+        let mut data = fundraiser.try_borrow_mut_data()?;
+        let fundraiser_account = Self::load_mut(&mut data)?;
+
+        fundraiser_account.update_status(current_time)?;
+
+        Ok(*fundraiser_account)
+    }
+
+    #[inline]
+    pub fn increase_amount(
+        fundraiser: &AccountInfo,
+        amount_to_increase: u64
     ) -> ProgramResult {
-        let mut fundraiser_account = Self::try_from_slice(&fundraiser.try_borrow_mut_data()?)?;
+        let mut data = fundraiser.try_borrow_mut_data()?;
+        let fundraiser_account = Self::load_mut(&mut data)?;
 
-        if fundraiser_account.duration > ((current_time - fundraiser_account.time_started) / SECONDS_TO_DAYS) as u8 {
-            return Err(FundraiserError::FundraiserEnded.into())
-        }
+        fundraiser_account.require_active()?;
 
-        fundraiser_account.current_amount += amount_to_increase;
+        let current_amount = fundraiser_account.current_amount()
+            .checked_add(amount_to_increase)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
 
-        fundraiser_account.serialize(&mut *fundraiser.data.borrow_mut())?;
+        fundraiser_account.set_current_amount(current_amount);
         
         Ok(())
     }
-}
\ No newline at end of file
+
+    #[inline]
+    pub fn amount_to_raise(&self) -> u64 {
+        u64::from_le_bytes(self.amount_to_raise)
+    }
+
+    #[inline]
+    pub fn set_amount_to_raise(&mut self, amount_to_raise: u64) {
+        self.amount_to_raise = amount_to_raise.to_le_bytes();
+    }
+
+    #[inline]
+    pub fn current_amount(&self) -> u64 {
+        u64::from_le_bytes(self.current_amount)
+    }
+
+    #[inline]
+    pub fn set_current_amount(&mut self, current_amount: u64) {
+        self.current_amount = current_amount.to_le_bytes();
+    }
+
+    #[inline]
+    pub fn time_started(&self) -> i64 {
+        i64::from_le_bytes(self.time_started)
+    }
+
+    #[inline]
+    pub fn set_time_started(&mut self, time_started: i64) {
+        self.time_started = time_started.to_le_bytes();
+    }
+
+    #[inline]
+    pub fn pledge_mode(&self) -> bool {
+        self.pledge_mode != 0
+    }
+
+    #[inline]
+    pub fn has_milestones(&self) -> bool {
+        self.has_milestones != 0
+    }
+
+    #[inline]
+    pub fn voting_period(&self) -> i64 {
+        i64::from_le_bytes(self.voting_period)
+    }
+
+    #[inline]
+    pub fn proposal_count(&self) -> u64 {
+        u64::from_le_bytes(self.proposal_count)
+    }
+
+    #[inline]
+    pub fn set_proposal_count(&mut self, proposal_count: u64) {
+        self.proposal_count = proposal_count.to_le_bytes();
+    }
+
+    #[inline]
+    pub fn status(&self) -> Result<LifecycleStatus, ProgramError> {
+        LifecycleStatus::try_from(self.status)
+    }
+
+    /// The only place the status changes, rejects any move the lifecycle does not allow.
+    #[inline]
+    pub fn transition(&mut self, to: LifecycleStatus) -> ProgramResult {
+        if !self.status()?.can_transition_to(to) {
+            return Err(FundraiserError::InvalidStatusTransition.into())
+        }
+
+        self.status = to as u8;
+
+        Ok(())
+    }
+
+    /// Starts the fundraiser once `time_started` is reached and settles its outcome once
+    /// the deadline passes. Flexible fundraisers always succeed.
+    #[inline]
+    pub fn update_status(&mut self, current_time: i64) -> Result<LifecycleStatus, ProgramError> {
+        if self.status()? == LifecycleStatus::Pending && current_time >= self.time_started() {
+            self.transition(LifecycleStatus::Active)?;
+        }
+
+        if self.status()? == LifecycleStatus::Active && self.has_ended(current_time) {
+            if self.funding_mode() == FundingMode::Flexible || self.check_success().is_ok() {
+                self.transition(LifecycleStatus::Succeeded)?;
+            } else {
+                self.transition(LifecycleStatus::Failed)?;
+            }
+        }
+
+        self.status()
+    }
+
+    /// Fundraisers created before headers had to raise the whole amount, the other fields
+    /// they did not have start zeroed, which is their default. `migrate` runs this once
+    /// the fields have been moved behind the header.
+    #[inline]
+    pub fn migrate_v0(&mut self) {
+        self.min_success_percentage = 100;
+    }
+
+    /// Contributions are only taken while the fundraiser is active.
+    #[inline]
+    pub fn require_active(&self) -> ProgramResult {
+        match self.status()? {
+            LifecycleStatus::Active => Ok(()),
+            LifecycleStatus::Pending => Err(FundraiserError::FundraiserNotStarted.into()),
+            LifecycleStatus::Cancelled => Err(FundraiserError::FundraiserCancelled.into()),
+            _ => Err(FundraiserError::FundraiserEnded.into()),
+        }
+    }
+
+    /// The maker is only paid out, and pledges only collected, once the fundraiser succeeded.
+    #[inline]
+    pub fn require_succeeded(&self) -> ProgramResult {
+        match self.status()? {
+            LifecycleStatus::Succeeded => Ok(()),
+            LifecycleStatus::Pending | LifecycleStatus::Active => Err(FundraiserError::FundraiserNotEnded.into()),
+            LifecycleStatus::Failed => Err(FundraiserError::AmountRaisedNotEnough.into()),
+            LifecycleStatus::Cancelled => Err(FundraiserError::FundraiserCancelled.into()),
+            LifecycleStatus::Settled => Err(FundraiserError::FundraiserAlreadyClosed.into()),
+        }
+    }
+
+    #[inline]
+    pub fn stream_duration(&self) -> i64 {
+        i64::from_le_bytes(self.stream_duration)
+    }
+
+    #[inline]
+    pub fn funding_mode(&self) -> FundingMode {
+        match self.funding_mode {
+            1 => FundingMode::Flexible,
+            _ => FundingMode::AllOrNothing,
+        }
+    }
+
+    #[inline]
+    pub fn contributor_count(&self) -> u64 {
+        u64::from_le_bytes(self.contributor_count)
+    }
+
+    #[inline]
+    pub fn set_contributor_count(&mut self, contributor_count: u64) {
+        self.contributor_count = contributor_count.to_le_bytes();
+    }
+
+    #[inline]
+    pub fn fee_bps(&self) -> u16 {
+        u16::from_le_bytes(self.fee_bps)
+    }
+
+    #[inline]
+    pub fn paused(&self) -> bool {
+        self.paused != 0
+    }
+
+    #[inline]
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused as u8;
+    }
+
+    #[inline]
+    pub fn mint_extensions(&self) -> u16 {
+        u16::from_le_bytes(self.mint_extensions)
+    }
+
+    #[inline]
+    pub fn ata_vault(&self) -> bool {
+        self.ata_vault != 0
+    }
+
+    #[inline]
+    pub fn pending_pledges(&self) -> u64 {
+        u64::from_le_bytes(self.pending_pledges)
+    }
+
+    #[inline]
+    pub fn set_pending_pledges(&mut self, pending_pledges: u64) {
+        self.pending_pledges = pending_pledges.to_le_bytes();
+    }
+
+    /// Checks `current_amount` reached the success threshold, `amount_to_raise` itself
+    /// is only the target shown to contributors.
+    #[inline]
+    pub fn check_success(&self) -> ProgramResult {
+        let threshold = self.amount_to_raise() as u128 * self.min_success_percentage as u128;
+
+        if (self.current_amount() as u128) * 100 >= threshold {
+            return Ok(())
+        }
+
+        if self.min_success_percentage < 100 {
+            Err(FundraiserError::BelowSuccessThreshold.into())
+        } else {
+            Err(FundraiserError::AmountRaisedNotEnough.into())
+        }
+    }
+
+    /// Timestamp at which the fundraiser stops accepting contributions.
+    #[inline]
+    pub fn deadline(&self) -> i64 {
+        self.time_started() + self.duration as i64 * SECONDS_TO_DAYS
+    }
+
+    /// Whether the full `duration` (in days) has elapsed since `time_started`.
+    #[inline]
+    pub fn has_ended(&self, current_time: i64) -> bool {
+        current_time >= self.deadline()
+    }
+
+    /// Phase reported to integrators, call `update_status` first.
+    #[inline]
+    pub fn phase(&self) -> Result<FundraiserPhase, ProgramError> {
+        Ok(match self.status()? {
+            LifecycleStatus::Pending | LifecycleStatus::Active => FundraiserPhase::Active,
+            LifecycleStatus::Succeeded | LifecycleStatus::Settled => FundraiserPhase::Succeeded,
+            LifecycleStatus::Failed => FundraiserPhase::Failed,
+            LifecycleStatus::Cancelled => FundraiserPhase::Cancelled,
+        })
+    }
+}
@@ -0,0 +1,128 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use shank::{ShankAccount, ShankType};
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, program_error::ProgramError, pubkey::Pubkey
+};
+
+use crate::constants::MAX_MILESTONES;
+
+use super::{check_header, AccountDiscriminator, ACCOUNT_HEADER_LEN};
+
+#[derive(Debug, Clone, Copy, Default, BorshDeserialize, BorshSerialize, ShankType)]
+pub struct Milestone {
+    /// Percentage of the raised amount released with this milestone
+    pub share: u8,
+    pub approved: bool,
+    pub released: bool,
+}
+
+#[derive(Debug, Clone, Copy, BorshDeserialize, BorshSerialize, ShankAccount)]
+pub struct Milestones {
+    pub discriminator: u8,
+    pub version: u8,
+    /// Key allowed to approve milestones, `Pubkey::default()` when there is none
+    pub arbiter: Pubkey,
+    /// Vault balance when the fundraiser was closed successfully, 0 until then
+    pub total_raised: u64,
+    pub released_amount: u64,
+    pub abandoned: bool,
+    pub count: u8,
+    pub milestones: [Milestone; MAX_MILESTONES],
+    pub bump: u8,
+    /// Sum of the contributions when the fundraiser was closed successfully, refunds are
+    /// shared out pro-rata to it
+    pub total_contributed: u64,
+}
+
+impl Milestones {
+    pub const LEN: usize = ACCOUNT_HEADER_LEN + 32 + 8 + 8 + 1 + 1 + 3 * MAX_MILESTONES + 1 + 8;
+    pub const VERSION: u8 = 1;
+
+    #[inline]
+    pub fn init(
+        milestones: &AccountInfo,
+        arbiter: &Pubkey,
+        shares: &[u8],
+        bump: u8
+    ) -> ProgramResult {
+        let mut milestones_data = Milestones {
+            discriminator: AccountDiscriminator::Milestones as u8,
+            version: Self::VERSION,
+            arbiter: *arbiter,
+            total_raised: 0,
+            released_amount: 0,
+            abandoned: false,
+            count: shares.len() as u8,
+            milestones: [Milestone::default(); MAX_MILESTONES],
+            bump,
+            total_contributed: 0,
+        };
+
+        for (milestone, share) in milestones_data.milestones.iter_mut().zip(shares) {
+            milestone.share = *share;
+        }
+
+        milestones_data.serialize(&mut *milestones.data.borrow_mut())?;
+
+        Ok(())
+    }
+
+    /// Deserializes the account, checking it is the milestones PDA of `fundraiser`.
+    #[inline]
+    pub fn load(
+        milestones: &AccountInfo,
+        fundraiser: &Pubkey
+    ) -> Result<Self, ProgramError> {
+        if milestones.owner != &crate::ID {
+            return Err(ProgramError::InvalidAccountOwner)
+        }
+
+        let data = milestones.try_borrow_data()?;
+
+        check_header(&data, AccountDiscriminator::Milestones, Self::VERSION)?;
+
+        let milestones_data = Self::try_from_slice(&data)?;
+
+        let expected_pda = Pubkey::create_program_address(
+            &[b"milestones", fundraiser.as_ref(), &[milestones_data.bump]],
+            &crate::ID
+        )?;
+
+        if expected_pda != *milestones.key {
+            return Err(ProgramError::InvalidSeeds)
+        }
+
+        Ok(milestones_data)
+    }
+
+    #[inline]
+    pub fn is_funded(&self) -> bool {
+        self.total_raised > 0
+    }
+
+    /// Amount released by the milestone at `index`. The last milestone to be released
+    /// sweeps whatever rounding left behind.
+    pub fn tranche_amount(&self, index: usize) -> u64 {
+        let unreleased = self.milestones[..self.count as usize]
+            .iter()
+            .filter(|milestone| !milestone.released)
+            .count();
+
+        if unreleased == 1 {
+            return self.total_raised - self.released_amount
+        }
+
+        (self.total_raised as u128 * self.milestones[index].share as u128 / 100) as u64
+    }
+
+    /// Share of `amount` that is still held back by unreleased milestones. `total_raised`
+    /// is what was left after the fee, so the contributions are the denominator, otherwise
+    /// refunds would add up to more than the vault holds.
+    pub fn refundable_amount(&self, amount: u64) -> u64 {
+        let remaining = self.total_raised - self.released_amount;
+
+        (amount as u128 * remaining as u128)
+            .checked_div(self.total_contributed as u128)
+            .unwrap_or(0) as u64
+    }
+}
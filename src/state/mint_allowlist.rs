@@ -0,0 +1,107 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use shank::ShankAccount;
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, program_error::ProgramError, pubkey::Pubkey
+};
+
+use crate::{constants::MAX_ALLOWED_MINTS, error::FundraiserError};
+
+use super::{check_header, AccountDiscriminator, ACCOUNT_HEADER_LEN};
+
+/// Mints fundraisers can be created for, a single PDA at `[b"mint_allowlist"]` curated by
+/// the config admin.
+#[derive(Debug, Clone, Copy, BorshDeserialize, BorshSerialize, ShankAccount)]
+pub struct MintAllowlist {
+    pub discriminator: u8,
+    pub version: u8,
+    pub count: u8,
+    /// The first `count` entries are the allowed mints
+    pub mints: [Pubkey; MAX_ALLOWED_MINTS],
+    pub bump: u8,
+}
+
+impl MintAllowlist {
+    pub const LEN: usize = ACCOUNT_HEADER_LEN + 1 + 32 * MAX_ALLOWED_MINTS + 1;
+    pub const VERSION: u8 = 1;
+
+    #[inline]
+    pub fn init(
+        allowlist: &AccountInfo,
+        bump: u8
+    ) -> ProgramResult {
+        let allowlist_data = MintAllowlist {
+            discriminator: AccountDiscriminator::MintAllowlist as u8,
+            version: Self::VERSION,
+            count: 0,
+            mints: [Pubkey::default(); MAX_ALLOWED_MINTS],
+            bump,
+        };
+
+        allowlist_data.serialize(&mut *allowlist.data.borrow_mut())?;
+
+        Ok(())
+    }
+
+    /// Deserializes the account, checking it is the allowlist PDA.
+    #[inline]
+    pub fn load(
+        allowlist: &AccountInfo
+    ) -> Result<Self, ProgramError> {
+        if allowlist.owner != &crate::ID {
+            return Err(ProgramError::InvalidAccountOwner)
+        }
+
+        let data = allowlist.try_borrow_data()?;
+
+        check_header(&data, AccountDiscriminator::MintAllowlist, Self::VERSION)?;
+
+        let allowlist_data = Self::try_from_slice(&data)?;
+
+        let expected_pda = Pubkey::create_program_address(
+            &[b"mint_allowlist", &[allowlist_data.bump]],
+            &crate::ID
+        )?;
+
+        if expected_pda != *allowlist.key {
+            return Err(ProgramError::InvalidSeeds)
+        }
+
+        Ok(allowlist_data)
+    }
+
+    #[inline]
+    pub fn contains(&self, mint: &Pubkey) -> bool {
+        self.mints[..self.count as usize].contains(mint)
+    }
+
+    /// Adds `mint`, doing nothing if it is already allowed.
+    #[inline]
+    pub fn add(&mut self, mint: &Pubkey) -> ProgramResult {
+        if self.contains(mint) {
+            return Ok(())
+        }
+
+        if self.count as usize == MAX_ALLOWED_MINTS {
+            return Err(FundraiserError::MintAllowlistFull.into())
+        }
+
+        self.mints[self.count as usize] = *mint;
+        self.count += 1;
+
+        Ok(())
+    }
+
+    /// Removes `mint`, moving the last entry into its place.
+    #[inline]
+    pub fn remove(&mut self, mint: &Pubkey) -> ProgramResult {
+        let Some(index) = self.mints[..self.count as usize].iter().position(|allowed| allowed == mint) else {
+            return Err(FundraiserError::MintNotAllowed.into())
+        };
+
+        self.count -= 1;
+        self.mints[index] = self.mints[self.count as usize];
+        self.mints[self.count as usize] = Pubkey::default();
+
+        Ok(())
+    }
+}
@@ -1,2 +1,65 @@
 pub mod fundraiser;
-pub mod contributor;
\ No newline at end of file
+pub mod contributor;
+pub mod milestones;
+pub mod proposal;
+pub mod vote_record;
+pub mod stream;
+pub mod config;
+pub mod mint_allowlist;
+pub mod rent_pool;
+
+use solana_program::{entrypoint::ProgramResult, program_error::ProgramError};
+
+use crate::error::FundraiserError;
+
+/// Every account owned by this program starts with its discriminator followed by
+/// its layout version.
+pub const ACCOUNT_HEADER_LEN: usize = 2;
+
+/// First byte of every account owned by this program. Starts at 1 so zeroed data
+/// never passes for an initialized account.
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccountDiscriminator {
+    Fundraiser = 1,
+    Contributor = 2,
+    Milestones = 3,
+    Proposal = 4,
+    VoteRecord = 5,
+    Stream = 6,
+    Config = 7,
+    MintAllowlist = 8,
+}
+
+impl TryFrom<&u8> for AccountDiscriminator {
+    type Error = ProgramError;
+
+    fn try_from(value: &u8) -> Result<Self, Self::Error> {
+        match value {
+            1 => Ok(AccountDiscriminator::Fundraiser),
+            2 => Ok(AccountDiscriminator::Contributor),
+            3 => Ok(AccountDiscriminator::Milestones),
+            4 => Ok(AccountDiscriminator::Proposal),
+            5 => Ok(AccountDiscriminator::VoteRecord),
+            6 => Ok(AccountDiscriminator::Stream),
+            7 => Ok(AccountDiscriminator::Config),
+            8 => Ok(AccountDiscriminator::MintAllowlist),
+            _ => Err(FundraiserError::InvalidAccountDiscriminator.into())
+        }
+    }
+}
+
+/// Checks the header at the start of `data`, an account of the right type but with an
+/// older layout has to go through `migrate` first.
+#[inline]
+pub fn check_header(
+    data: &[u8],
+    discriminator: AccountDiscriminator,
+    version: u8
+) -> ProgramResult {
+    match data {
+        [d, v, ..] if *d == discriminator as u8 && *v == version => Ok(()),
+        [d, _, ..] if *d == discriminator as u8 => Err(FundraiserError::AccountVersionMismatch.into()),
+        _ => Err(FundraiserError::InvalidAccountDiscriminator.into()),
+    }
+}
@@ -0,0 +1,110 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use shank::{ShankAccount, ShankType};
+use solana_program::{
+    account_info::AccountInfo, clock::Clock, entrypoint::ProgramResult, program_error::ProgramError, pubkey::Pubkey, sysvar::Sysvar
+};
+
+use crate::error::FundraiserError;
+
+use super::{check_header, fundraiser::Fundraiser, AccountDiscriminator, ACCOUNT_HEADER_LEN};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, BorshDeserialize, BorshSerialize, ShankType)]
+pub enum ProposalKind {
+    /// Approves the milestone at index `argument`, maker only
+    ReleaseTranche,
+    /// Extends the fundraiser by `argument` days, maker only
+    ExtendDeadline,
+    /// Cancels the fundraiser so every contributor can refund, any contributor
+    RefundEveryone,
+}
+
+#[derive(Debug, Clone, Copy, BorshDeserialize, BorshSerialize, ShankAccount)]
+pub struct Proposal {
+    pub discriminator: u8,
+    pub version: u8,
+    pub fundraiser: Pubkey,
+    pub proposer: Pubkey,
+    pub kind: ProposalKind,
+    pub argument: u8,
+    pub votes_for: u64,
+    pub votes_against: u64,
+    pub voting_ends: i64,
+    pub executed: bool,
+    pub bump: u8,
+    /// `current_amount` of the fundraiser when the proposal was created, the quorum is
+    /// measured against it
+    pub total_weight: u64,
+    /// Slot the proposal was created in, contributions made since cannot vote on it
+    pub created_slot: u64,
+}
+
+impl Proposal {
+    pub const LEN: usize = ACCOUNT_HEADER_LEN + 32 + 32 + 1 + 1 + 8 + 8 + 8 + 1 + 1 + 8 + 8;
+    pub const VERSION: u8 = 1;
+
+    /// Opens a proposal on `fundraiser` for its voting period, snapshotting its
+    /// `current_amount` as the total weight and the slot it is created in.
+    #[inline]
+    pub fn init(
+        proposal: &AccountInfo,
+        fundraiser: &Pubkey,
+        fundraiser_account: &Fundraiser,
+        proposer: &Pubkey,
+        kind: ProposalKind,
+        argument: u8,
+        bump: u8
+    ) -> ProgramResult {
+        let clock = Clock::get()?;
+
+        let proposal_data = Proposal {
+            discriminator: AccountDiscriminator::Proposal as u8,
+            version: Self::VERSION,
+            fundraiser: *fundraiser,
+            proposer: *proposer,
+            kind,
+            argument,
+            votes_for: 0,
+            votes_against: 0,
+            voting_ends: clock.unix_timestamp + fundraiser_account.voting_period(),
+            executed: false,
+            bump,
+            total_weight: fundraiser_account.current_amount(),
+            created_slot: clock.slot,
+        };
+
+        proposal_data.serialize(&mut *proposal.data.borrow_mut())?;
+
+        Ok(())
+    }
+
+    /// Deserializes the account, checking it is owned by this program and belongs to `fundraiser`.
+    #[inline]
+    pub fn load(
+        proposal: &AccountInfo,
+        fundraiser: &Pubkey
+    ) -> Result<Self, ProgramError> {
+        if proposal.owner != &crate::ID {
+            return Err(ProgramError::InvalidAccountOwner)
+        }
+
+        let data = proposal.try_borrow_data()?;
+
+        check_header(&data, AccountDiscriminator::Proposal, Self::VERSION)?;
+
+        let proposal_data = Self::try_from_slice(&data)?;
+
+        if proposal_data.fundraiser != *fundraiser {
+            return Err(FundraiserError::InvalidProposal.into())
+        }
+
+        Ok(proposal_data)
+    }
+
+    /// Whether enough of the weight snapshotted at creation voted for the proposal to pass.
+    #[inline]
+    pub fn quorum_reached(&self, quorum: u8) -> bool {
+        let votes = self.votes_for as u128 + self.votes_against as u128;
+
+        votes * 100 >= self.total_weight as u128 * quorum as u128
+    }
+}
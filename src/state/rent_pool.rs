@@ -0,0 +1,29 @@
+use solana_program::{
+    account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey
+};
+
+/// Lamports the maker set aside to pay the rent of new contributor accounts. The pool is
+/// a data-less system account at `[b"rent_pool", fundraiser]`, so it can fund
+/// `create_account` by signing with its seeds, and sponsored contributor accounts hand
+/// their rent back to it when they are closed.
+pub struct RentPool;
+
+impl RentPool {
+    /// Checks the account is the rent pool of the fundraiser and returns its bump.
+    #[inline]
+    pub fn check(
+        rent_pool: &AccountInfo,
+        fundraiser: &Pubkey
+    ) -> Result<u8, ProgramError> {
+        let (rent_pool_pda, bump) = Pubkey::find_program_address(
+            &[b"rent_pool", fundraiser.as_ref()],
+            &crate::ID
+        );
+
+        if rent_pool_pda != *rent_pool.key {
+            return Err(ProgramError::InvalidSeeds)
+        }
+
+        Ok(bump)
+    }
+}
@@ -0,0 +1,84 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use shank::ShankAccount;
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, program_error::ProgramError, pubkey::Pubkey
+};
+
+use super::{check_header, AccountDiscriminator, ACCOUNT_HEADER_LEN};
+
+/// Linear vesting of the raised amount to the maker, created when the fundraiser succeeds.
+#[derive(Debug, Clone, Copy, BorshDeserialize, BorshSerialize, ShankAccount)]
+pub struct Stream {
+    pub discriminator: u8,
+    pub version: u8,
+    pub total: u64,
+    pub claimed: u64,
+    pub start: i64,
+    pub duration: i64,
+    pub bump: u8,
+}
+
+impl Stream {
+    pub const LEN: usize = ACCOUNT_HEADER_LEN + 8 + 8 + 8 + 8 + 1;
+    pub const VERSION: u8 = 1;
+
+    #[inline]
+    pub fn init(
+        stream: &AccountInfo,
+        total: u64,
+        start: i64,
+        duration: i64,
+        bump: u8
+    ) -> ProgramResult {
+        let stream_data = Stream {
+            discriminator: AccountDiscriminator::Stream as u8,
+            version: Self::VERSION,
+            total,
+            claimed: 0,
+            start,
+            duration,
+            bump,
+        };
+
+        stream_data.serialize(&mut *stream.data.borrow_mut())?;
+
+        Ok(())
+    }
+
+    /// Deserializes the account, checking it is the stream PDA of `fundraiser`.
+    #[inline]
+    pub fn load(
+        stream: &AccountInfo,
+        fundraiser: &Pubkey
+    ) -> Result<Self, ProgramError> {
+        if stream.owner != &crate::ID {
+            return Err(ProgramError::InvalidAccountOwner)
+        }
+
+        let data = stream.try_borrow_data()?;
+
+        check_header(&data, AccountDiscriminator::Stream, Self::VERSION)?;
+
+        let stream_data = Self::try_from_slice(&data)?;
+
+        let expected_pda = Pubkey::create_program_address(
+            &[b"stream", fundraiser.as_ref(), &[stream_data.bump]],
+            &crate::ID
+        )?;
+
+        if expected_pda != *stream.key {
+            return Err(ProgramError::InvalidSeeds)
+        }
+
+        Ok(stream_data)
+    }
+
+    /// Amount vested at `current_time` that has not been claimed yet.
+    pub fn claimable(&self, current_time: i64) -> u64 {
+        let elapsed = (current_time - self.start).clamp(0, self.duration);
+
+        let vested = (self.total as u128 * elapsed as u128 / self.duration as u128) as u64;
+
+        vested - self.claimed
+    }
+}
@@ -0,0 +1,40 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use shank::ShankAccount;
+use solana_program::{account_info::AccountInfo, entrypoint::ProgramResult};
+
+use super::{AccountDiscriminator, ACCOUNT_HEADER_LEN};
+
+/// Marks that a contributor voted on a proposal, its existence prevents double voting.
+#[derive(Debug, Clone, Copy, BorshDeserialize, BorshSerialize, ShankAccount)]
+pub struct VoteRecord {
+    pub discriminator: u8,
+    pub version: u8,
+    pub approve: bool,
+    pub weight: u64,
+    pub bump: u8,
+}
+
+impl VoteRecord {
+    pub const LEN: usize = ACCOUNT_HEADER_LEN + 1 + 8 + 1;
+    pub const VERSION: u8 = 1;
+
+    #[inline]
+    pub fn init(
+        vote_record: &AccountInfo,
+        approve: bool,
+        weight: u64,
+        bump: u8
+    ) -> ProgramResult {
+        let vote_record_data = VoteRecord {
+            discriminator: AccountDiscriminator::VoteRecord as u8,
+            version: Self::VERSION,
+            approve,
+            weight,
+            bump,
+        };
+
+        vote_record_data.serialize(&mut *vote_record.data.borrow_mut())?;
+
+        Ok(())
+    }
+}
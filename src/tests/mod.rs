@@ -1,118 +1,2843 @@
-#[cfg(test)]
-mod tests {
-    use mollusk_svm::{program, Mollusk};
-    use solana_sdk::{
-        account::{
-            AccountSharedData, 
-            WritableAccount
-        }, instruction::{
-            AccountMeta, 
-            Instruction
-        }, program_option::COption, program_pack::Pack, 
-        pubkey::Pubkey,
-        pubkey
-    };
-    
-    const AMOUNT_TO_RAISE: u64 = 10_000_000;
-    const TODAY_TIMESTAMP: i64 = 1731704609;
-    const DURATION_DAYS: u8 = 2;
-    
-    #[test]
-    fn initialize() {
+use crate::{
+    error::FundraiserError,
+    instructions::{ConfigArgs, FundraiserInstructions, InitializeArgs},
+    state::{
+        config::Config,
+        contributor::{Contributor, PledgeStatus},
+        fundraiser::{Fundraiser, LifecycleStatus},
+        milestones::{Milestone, Milestones},
+        mint_allowlist::MintAllowlist,
+        AccountDiscriminator
+    },
+    token::inspect_mint
+};
+use mollusk_svm::{program, result::ProgramResult, Mollusk};
+use solana_sdk::{
+    account::{
+        AccountSharedData, 
+        ReadableAccount,
+        WritableAccount
+    }, account_info::AccountInfo, instruction::{
+        AccountMeta, 
+        Instruction
+    }, program_error::ProgramError, program_option::COption, program_pack::Pack, 
+    pubkey::Pubkey,
+    pubkey
+};
 
+const AMOUNT_TO_RAISE: u64 = 10_000_000;
+const TODAY_TIMESTAMP: i64 = 1731704609;
+const DURATION_DAYS: u8 = 2;
+const DEADLINE: i64 = TODAY_TIMESTAMP + DURATION_DAYS as i64 * 86400;
+
+/// Accounts shared by the instruction tests: a fundraiser of `maker` raising a mint
+/// with 6 decimals into its `[b"vault", fundraiser]` vault.
+struct Fixture {
+    mollusk: Mollusk,
+    program_id: Pubkey,
+    token_program: Pubkey,
+    token_program_account: AccountSharedData,
+    system_program: Pubkey,
+    system_program_account: AccountSharedData,
+    associated_token_program_account: AccountSharedData,
+    maker: Pubkey,
+    mint_to_raise: Pubkey,
+    fundraiser: Pubkey,
+    bump: u8,
+    vault: Pubkey,
+    config: Pubkey,
+    config_bump: u8,
+    fee_recipient: Pubkey,
+    rent_pool: Pubkey,
+    /// Whether the config passed to `contribute` and `checker` pauses the program
+    config_paused: bool,
+    /// Lamports sent to the vault passed to `contribute` without being synced
+    unsynced_lamports: u64,
+    /// Balance of the rent pool passed to `contribute`
+    rent_pool_lamports: u64,
+    /// Fee of the token-2022 mint in basis points, 0 raises a token program mint
+    transfer_fee_bps: u16,
+    /// Hook program the token-2022 mint runs on transfers
+    transfer_hook_program: Option<Pubkey>,
+}
+
+impl Fixture {
+    fn new() -> Self {
         let program_id = pubkey!("22222222222222222222222222222222222222222222");
 
         let mut mollusk = Mollusk::new(&program_id, "target/deploy/native_fundraiser");
         mollusk_token::token::add_program(&mut mollusk);
+        mollusk_token::associated_token::add_program(&mut mollusk);
+        mollusk.sysvars.clock.unix_timestamp = TODAY_TIMESTAMP;
 
-        let (
-            token_program, 
-            token_program_account
-        ) = mollusk_token::token::keyed_account();
-
-        let (
-            system_program, 
-            system_program_account
-        ) = program::keyed_account_for_system_program();
+        let (token_program, token_program_account) = mollusk_token::token::keyed_account();
+        let (system_program, system_program_account) = program::keyed_account_for_system_program();
+        let (_, associated_token_program_account) = mollusk_token::associated_token::keyed_account();
 
         let maker = Pubkey::new_unique();
-        let mint_to_raise = Pubkey::new_unique();
-        let mut mint_to_raise_account = AccountSharedData::new(
-            mollusk.sysvars.rent.minimum_balance(spl_token::state::Mint::LEN), 
-            spl_token::state::Mint::LEN,
-            &token_program
-        );
-        solana_sdk::program_pack::Pack::pack(
-            spl_token::state::Mint {
-                mint_authority: COption::None,
-                supply: 100_000_000_000,
-                decimals: 6,
-                is_initialized: true,
-                freeze_authority: COption::None
+        let (fundraiser, bump) = Pubkey::find_program_address(
+            &[b"fundraiser", maker.as_ref()],
+            &program_id
+        );
+        let (vault, _) = Pubkey::find_program_address(&[b"vault", fundraiser.as_ref()], &program_id);
+        let (config, config_bump) = Pubkey::find_program_address(&[b"config"], &program_id);
+        let (rent_pool, _) = Pubkey::find_program_address(&[b"rent_pool", fundraiser.as_ref()], &program_id);
+
+        Self {
+            mollusk,
+            program_id,
+            token_program,
+            token_program_account,
+            system_program,
+            system_program_account,
+            associated_token_program_account,
+            maker,
+            mint_to_raise: Pubkey::new_unique(),
+            fundraiser,
+            bump,
+            vault,
+            config,
+            config_bump,
+            fee_recipient: Pubkey::new_unique(),
+            rent_pool,
+            config_paused: false,
+            unsynced_lamports: 0,
+            rent_pool_lamports: 0,
+            transfer_fee_bps: 0,
+            transfer_hook_program: None,
+        }
+    }
+
+    /// Fixture raising a token-2022 mint that withholds `transfer_fee_bps` of every
+    /// transfer, its token accounts carry the withheld amount.
+    fn with_transfer_fee(transfer_fee_bps: u16) -> Self {
+        let mut fixture = Self::new();
+
+        mollusk_token::token2022::add_program(&mut fixture.mollusk);
+        (fixture.token_program, fixture.token_program_account) = mollusk_token::token2022::keyed_account();
+        fixture.transfer_fee_bps = transfer_fee_bps;
+
+        fixture
+    }
+
+    /// Fixture raising a token-2022 mint whose transfers run `hook_program`.
+    fn with_transfer_hook(hook_program: Pubkey) -> Self {
+        let mut fixture = Self::with_transfer_fee(0);
+        fixture.transfer_hook_program = Some(hook_program);
+
+        fixture
+    }
+
+    /// Fixture whose fundraiser keeps the raised tokens in its associated token account,
+    /// see `InitializeArgs::ata_vault`.
+    fn with_ata_vault() -> Self {
+        let mut fixture = Self::new();
+        fixture.vault = Fundraiser::vault_address(&fixture.fundraiser, &fixture.mint_to_raise, &fixture.token_program, true);
+
+        fixture
+    }
+
+    fn mint_account(&self) -> AccountSharedData {
+        use spl_token_2022::extension::{
+            transfer_fee::{TransferFee, TransferFeeConfig},
+            transfer_hook::TransferHook,
+            BaseStateWithExtensionsMut,
+            ExtensionType,
+            StateWithExtensionsMut
+        };
+
+        let mut data = vec![0; spl_token::state::Mint::LEN];
+
+        let mut extension_types = vec![];
+        if self.transfer_fee_bps > 0 {
+            extension_types.push(ExtensionType::TransferFeeConfig);
+        }
+        if self.transfer_hook_program.is_some() {
+            extension_types.push(ExtensionType::TransferHook);
+        }
+
+        if !extension_types.is_empty() {
+            data = vec![0; ExtensionType::try_calculate_account_len::<spl_token_2022::state::Mint>(
+                &extension_types
+            ).unwrap()];
+
+            let mut extensions = StateWithExtensionsMut::<spl_token_2022::state::Mint>::unpack_uninitialized(&mut data).unwrap();
+
+            if self.transfer_fee_bps > 0 {
+                let transfer_fee = TransferFee {
+                    epoch: 0.into(),
+                    maximum_fee: u64::MAX.into(),
+                    transfer_fee_basis_points: self.transfer_fee_bps.into(),
+                };
+                let config = extensions.init_extension::<TransferFeeConfig>(true).unwrap();
+                config.older_transfer_fee = transfer_fee;
+                config.newer_transfer_fee = transfer_fee;
+            }
+
+            if let Some(hook_program) = self.transfer_hook_program {
+                extensions.init_extension::<TransferHook>(true).unwrap().program_id = Some(hook_program).try_into().unwrap();
+            }
+
+            extensions.init_account_type().unwrap();
+        }
+
+        spl_token::state::Mint {
+            mint_authority: COption::None,
+            supply: 100_000_000_000,
+            decimals: 6,
+            is_initialized: true,
+            freeze_authority: COption::None
+        }.pack_into_slice(&mut data[..spl_token::state::Mint::LEN]);
+
+        let mut account = AccountSharedData::new(
+            self.mollusk.sysvars.rent.minimum_balance(data.len()),
+            data.len(),
+            &self.token_program
+        );
+        account.set_data_from_slice(&data);
+
+        account
+    }
+
+    fn token_state(&self, owner: &Pubkey, amount: u64) -> spl_token::state::Account {
+        spl_token::state::Account {
+            mint: self.mint_to_raise,
+            owner: *owner,
+            amount,
+            delegate: COption::None,
+            state: spl_token::state::AccountState::Initialized,
+            is_native: if crate::token::is_native_mint(&self.mint_to_raise) {
+                COption::Some(self.mollusk.sysvars.rent.minimum_balance(spl_token::state::Account::LEN))
+            } else {
+                COption::None
             },
-            mint_to_raise_account.data_as_mut_slice()
+            delegated_amount: 0,
+            close_authority: COption::None
+        }
+    }
+
+    fn token_account(&self, state: spl_token::state::Account) -> AccountSharedData {
+        self.token_account_withholding(state, 0)
+    }
+
+    /// Token account holding `withheld` in transfer fees, which only a transfer fee
+    /// mint keeps track of. It carries the account extensions the mint requires.
+    fn token_account_withholding(&self, state: spl_token::state::Account, withheld: u64) -> AccountSharedData {
+        use spl_token_2022::extension::{
+            transfer_fee::TransferFeeAmount,
+            transfer_hook::TransferHookAccount,
+            BaseStateWithExtensionsMut,
+            ExtensionType,
+            StateWithExtensionsMut
+        };
+
+        let mut data = vec![0; spl_token::state::Account::LEN];
+
+        let mut extension_types = vec![];
+        if self.transfer_fee_bps > 0 {
+            extension_types.push(ExtensionType::TransferFeeAmount);
+        }
+        if self.transfer_hook_program.is_some() {
+            extension_types.push(ExtensionType::TransferHookAccount);
+        }
+
+        if !extension_types.is_empty() {
+            data = vec![0; ExtensionType::try_calculate_account_len::<spl_token_2022::state::Account>(
+                &extension_types
+            ).unwrap()];
+
+            let mut extensions = StateWithExtensionsMut::<spl_token_2022::state::Account>::unpack_uninitialized(&mut data).unwrap();
+
+            if self.transfer_fee_bps > 0 {
+                extensions.init_extension::<TransferFeeAmount>(true).unwrap().withheld_amount = withheld.into();
+            }
+
+            if self.transfer_hook_program.is_some() {
+                extensions.init_extension::<TransferHookAccount>(true).unwrap();
+            }
+
+            extensions.init_account_type().unwrap();
+        }
+
+        state.pack_into_slice(&mut data[..spl_token::state::Account::LEN]);
+
+        // wrapped SOL accounts hold their balance in lamports on top of the rent
+        let wrapped = if state.is_native.is_some() { state.amount } else { 0 };
+
+        let mut account = AccountSharedData::new(
+            self.mollusk.sysvars.rent.minimum_balance(data.len()) + wrapped,
+            data.len(),
+            &self.token_program
+        );
+        account.set_data_from_slice(&data);
+
+        account
+    }
+
+    fn config_account(&self, fee_bps: u16, paused: bool) -> AccountSharedData {
+        let mut account = AccountSharedData::new(
+            self.mollusk.sysvars.rent.minimum_balance(Config::LEN),
+            Config::LEN,
+            &self.program_id
+        );
+        account.set_data_from_slice(&borsh::to_vec(&Config {
+            discriminator: AccountDiscriminator::Config as u8,
+            version: Config::VERSION,
+            admin: Pubkey::new_unique(),
+            paused,
+            fee_bps,
+            fee_recipient: self.fee_recipient,
+            max_duration: 0,
+            max_amount_to_raise: 0,
+            bump: self.config_bump,
+            rejected_extensions: 0,
+        }).unwrap());
+
+        account
+    }
+
+    /// Fundraiser as `initialize` creates it from `args`, then changed by `update`.
+    fn fundraiser_account(
+        &self,
+        args: &InitializeArgs,
+        fee_bps: u16,
+        update: impl FnOnce(&mut Fundraiser)
+    ) -> AccountSharedData {
+        let mut lamports = self.mollusk.sysvars.rent.minimum_balance(Fundraiser::LEN);
+        let mut data = vec![0; Fundraiser::LEN];
+        let fundraiser_info = AccountInfo::new(
+            &self.fundraiser, false, true, &mut lamports, &mut data, &self.program_id, false, 0
+        );
+        Fundraiser::init(
+            &fundraiser_info,
+            &self.maker,
+            &self.mint_to_raise,
+            args,
+            self.bump,
+            fee_bps,
+            inspect_mint(self.mint_account().data()).unwrap(),
         ).unwrap();
+        drop(fundraiser_info);
 
-        let (fundraiser, _bump) = Pubkey::find_program_address(
-            &[b"fundraiser", maker.as_ref()],
-            &program_id
+        update(Fundraiser::load_mut(&mut data).unwrap());
+
+        let mut account = AccountSharedData::new(lamports, Fundraiser::LEN, &self.program_id);
+        account.set_data_from_slice(&data);
+
+        account
+    }
+
+    fn contributor_account(
+        &self,
+        contributor: &Pubkey,
+        amount: u64,
+        pledge_status: PledgeStatus
+    ) -> (Pubkey, AccountSharedData) {
+        let (contributor_account, bump) = Pubkey::find_program_address(
+            &[b"contributor", self.fundraiser.as_ref(), contributor.as_ref()],
+            &self.program_id
         );
-        let fundraiser_account = AccountSharedData::new(0, 0, &system_program);
 
-        let (vault, _) = Pubkey::find_program_address(
-            &[b"vault", fundraiser.as_ref()],
-            &program_id
+        let mut data = vec![0; Contributor::LEN];
+        let contributor_data = Contributor::new_mut(&mut data).unwrap();
+        contributor_data.set_amount(amount);
+        contributor_data.bump = bump;
+        contributor_data.set_pledge_status(pledge_status);
+        contributor_data.rent_payer = *contributor;
+
+        let mut account = AccountSharedData::new(
+            self.mollusk.sysvars.rent.minimum_balance(Contributor::LEN),
+            Contributor::LEN,
+            &self.program_id
         );
+        account.set_data_from_slice(&data);
 
-        let vault_account = AccountSharedData::new(
-            0,
+        (contributor_account, account)
+    }
+
+    fn milestones_account(&self, shares: &[u8]) -> (Pubkey, AccountSharedData) {
+        let (milestones, bump) = Pubkey::find_program_address(
+            &[b"milestones", self.fundraiser.as_ref()],
+            &self.program_id
+        );
+
+        let mut account = AccountSharedData::new(
+            self.mollusk.sysvars.rent.minimum_balance(Milestones::LEN),
+            Milestones::LEN,
+            &self.program_id
+        );
+        account.set_data_from_slice(&borsh::to_vec(&Milestones {
+            discriminator: AccountDiscriminator::Milestones as u8,
+            version: Milestones::VERSION,
+            arbiter: Pubkey::default(),
+            total_raised: 0,
+            released_amount: 0,
+            abandoned: false,
+            count: shares.len() as u8,
+            milestones: core::array::from_fn(|i| Milestone {
+                share: shares.get(i).copied().unwrap_or(0),
+                ..Milestone::default()
+            }),
+            bump,
+            total_contributed: 0,
+        }).unwrap());
+
+        (milestones, account)
+    }
+
+    /// Mint allowlist holding `mints`.
+    fn mint_allowlist_account(&self, mints: &[Pubkey]) -> (Pubkey, AccountSharedData) {
+        let (mint_allowlist, bump) = Pubkey::find_program_address(&[b"mint_allowlist"], &self.program_id);
+
+        let mut account = AccountSharedData::new(
+            self.mollusk.sysvars.rent.minimum_balance(MintAllowlist::LEN),
+            MintAllowlist::LEN,
+            &self.program_id
+        );
+        account.set_data_from_slice(&borsh::to_vec(&MintAllowlist {
+            discriminator: AccountDiscriminator::MintAllowlist as u8,
+            version: MintAllowlist::VERSION,
+            count: mints.len() as u8,
+            mints: core::array::from_fn(|i| mints.get(i).copied().unwrap_or_default()),
+            bump,
+        }).unwrap());
+
+        (mint_allowlist, account)
+    }
+
+    /// `initialize` of the fixture's fundraiser under `config`, with the fixture mint
+    /// allowed. `remaining` are appended to the accounts.
+    fn initialize(
+        &self,
+        args: InitializeArgs,
+        config: AccountSharedData,
+        remaining: Vec<(AccountMeta, AccountSharedData)>
+    ) -> mollusk_svm::result::InstructionResult {
+        self.initialize_with(args, config, self.mint_allowlist_account(&[self.mint_to_raise]), remaining)
+    }
+
+    /// `initialize` with the given mint allowlist.
+    fn initialize_with(
+        &self,
+        args: InitializeArgs,
+        config: AccountSharedData,
+        (mint_allowlist, mint_allowlist_account): (Pubkey, AccountSharedData),
+        remaining: Vec<(AccountMeta, AccountSharedData)>
+    ) -> mollusk_svm::result::InstructionResult {
+        let vault = Fundraiser::vault_address(&self.fundraiser, &self.mint_to_raise, &self.token_program, args.ata_vault);
+
+        let mut metas = vec![
+            AccountMeta::new(self.maker, true),
+            AccountMeta::new_readonly(self.mint_to_raise, false),
+            AccountMeta::new(self.fundraiser, false),
+            AccountMeta::new(vault, false),
+            AccountMeta::new_readonly(self.system_program, false),
+            AccountMeta::new_readonly(self.token_program, false),
+            AccountMeta::new_readonly(self.config, false),
+            AccountMeta::new_readonly(mint_allowlist, false),
+            AccountMeta::new(self.rent_pool, false),
+            AccountMeta::new_readonly(spl_associated_token_account::ID, false),
+        ];
+        let mut accounts = vec![
+            (self.maker, AccountSharedData::new(1_000_000_000_000, 0, &self.system_program)),
+            (self.mint_to_raise, self.mint_account()),
+            (self.fundraiser, AccountSharedData::new(0, 0, &self.system_program)),
+            (vault, AccountSharedData::new(0, 0, &self.system_program)),
+            (self.system_program, self.system_program_account.clone()),
+            (self.token_program, self.token_program_account.clone()),
+            (self.config, config),
+            (mint_allowlist, mint_allowlist_account),
+            (self.rent_pool, AccountSharedData::new(0, 0, &self.system_program)),
+            (spl_associated_token_account::ID, self.associated_token_program_account.clone()),
+        ];
+        for (meta, account) in remaining {
+            accounts.push((meta.pubkey, account));
+            metas.push(meta);
+        }
+
+        self.mollusk.process_instruction(
+            &self.instruction(FundraiserInstructions::Initialize(args), metas),
+            &accounts
+        )
+    }
+
+    /// `contribute` from `contributor`, who signs and pays. Contributions through an intent
+    /// are submitted by a `relayer` instead, along with the instructions sysvar holding the
+    /// signature.
+    fn contribute(
+        &self,
+        instruction: FundraiserInstructions,
+        contributor: &Pubkey,
+        contributor_account: AccountSharedData,
+        contributor_ata: spl_token::state::Account,
+        fundraiser_account: AccountSharedData,
+        relayer: Option<(&Pubkey, AccountSharedData)>
+    ) -> mollusk_svm::result::InstructionResult {
+        let contributor_ata_key = Pubkey::new_unique();
+        let contributor_account = (self.contributor_account(contributor, 0, PledgeStatus::Pending).0, contributor_account);
+        let (payer, instructions_sysvar) = relayer.unwrap_or((contributor, AccountSharedData::default()));
+
+        let mut vault = self.token_account(self.token_state(&self.fundraiser, 0));
+        vault.set_lamports(vault.lamports() + self.unsynced_lamports);
+
+        let metas = vec![
+            AccountMeta::new(*contributor, contributor == payer),
+            AccountMeta::new(contributor_account.0, false),
+            AccountMeta::new(contributor_ata_key, false),
+            AccountMeta::new(self.fundraiser, false),
+            AccountMeta::new(self.vault, false),
+            AccountMeta::new_readonly(self.token_program, false),
+            AccountMeta::new_readonly(self.system_program, false),
+            AccountMeta::new_readonly(self.config, false),
+            AccountMeta::new_readonly(self.mint_to_raise, false),
+            AccountMeta::new(self.rent_pool, false),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(solana_sdk::sysvar::instructions::ID, false),
+        ];
+        let mut accounts = vec![
+            (*contributor, AccountSharedData::new(1_000_000_000, 0, &self.system_program)),
+            contributor_account,
+            (contributor_ata_key, self.token_account(contributor_ata)),
+            (self.fundraiser, fundraiser_account),
+            (self.vault, vault),
+            (self.token_program, self.token_program_account.clone()),
+            (self.system_program, self.system_program_account.clone()),
+            (self.config, self.config_account(0, self.config_paused)),
+            (self.mint_to_raise, self.mint_account()),
+            (self.rent_pool, AccountSharedData::new(self.rent_pool_lamports, 0, &self.system_program)),
+            (solana_sdk::sysvar::instructions::ID, instructions_sysvar),
+        ];
+        if payer != contributor {
+            accounts.push((*payer, AccountSharedData::new(1_000_000_000, 0, &self.system_program)));
+        }
+
+        self.mollusk.process_instruction(&self.instruction(instruction, metas), &accounts)
+    }
+
+    fn instruction(&self, instruction: FundraiserInstructions, accounts: Vec<AccountMeta>) -> Instruction {
+        Instruction::new_with_bytes(self.program_id, &borsh::to_vec(&instruction).unwrap(), accounts)
+    }
+
+    /// Stands in for an optional account that is not used, generated clients pass the
+    /// program ID.
+    fn unused_account(&self) -> (AccountMeta, AccountSharedData) {
+        (AccountMeta::new_readonly(self.program_id, false), AccountSharedData::default())
+    }
+
+    /// Appends `remaining` to the accounts of an instruction whose optional accounts start
+    /// at index 10, passing the ones left out at the end as unused.
+    fn append_remaining(
+        &self,
+        metas: &mut Vec<AccountMeta>,
+        accounts: &mut Vec<(Pubkey, AccountSharedData)>,
+        mut remaining: Vec<(AccountMeta, AccountSharedData)>
+    ) {
+        while remaining.len() < 2 {
+            remaining.push(self.unused_account());
+        }
+
+        for (meta, account) in remaining {
+            if !accounts.iter().any(|(key, _)| *key == meta.pubkey) {
+                accounts.push((meta.pubkey, account));
+            }
+            metas.push(meta);
+        }
+    }
+
+    /// `checker` paying the maker out directly, `remaining` are appended to the accounts.
+    fn checker(
+        &self,
+        fundraiser_account: AccountSharedData,
+        vault_amount: u64,
+        fee_bps: u16,
+        remaining: Vec<(AccountMeta, AccountSharedData)>
+    ) -> mollusk_svm::result::InstructionResult {
+        let vault = self.token_account(self.token_state(&self.fundraiser, vault_amount));
+        let maker_ata = (Pubkey::new_unique(), self.token_account(self.token_state(&self.maker, 0)));
+
+        self.checker_with(fundraiser_account, vault, maker_ata, fee_bps, false, remaining)
+    }
+
+    /// `checker` with the given vault and maker token account.
+    fn checker_with(
+        &self,
+        fundraiser_account: AccountSharedData,
+        vault: AccountSharedData,
+        maker_ata: (Pubkey, AccountSharedData),
+        fee_bps: u16,
+        unwrap_sol: bool,
+        remaining: Vec<(AccountMeta, AccountSharedData)>
+    ) -> mollusk_svm::result::InstructionResult {
+        let fee_account = Pubkey::new_unique();
+
+        let mut metas = vec![
+            AccountMeta::new(self.maker, true),
+            AccountMeta::new(maker_ata.0, false),
+            AccountMeta::new(self.fundraiser, false),
+            AccountMeta::new(self.vault, false),
+            AccountMeta::new_readonly(self.token_program, false),
+            AccountMeta::new_readonly(self.config, false),
+            AccountMeta::new(fee_account, false),
+            AccountMeta::new(self.mint_to_raise, false),
+            AccountMeta::new_readonly(self.system_program, false),
+            AccountMeta::new_readonly(spl_associated_token_account::ID, false),
+        ];
+        let mut accounts = vec![
+            (self.maker, AccountSharedData::new(1_000_000_000, 0, &self.system_program)),
+            maker_ata,
+            (self.fundraiser, fundraiser_account),
+            (self.vault, vault),
+            (self.token_program, self.token_program_account.clone()),
+            (self.config, self.config_account(fee_bps, self.config_paused)),
+            (fee_account, self.token_account(self.token_state(&self.fee_recipient, 0))),
+            (self.mint_to_raise, self.mint_account()),
+            (self.system_program, self.system_program_account.clone()),
+            (spl_associated_token_account::ID, self.associated_token_program_account.clone()),
+        ];
+        self.append_remaining(&mut metas, &mut accounts, remaining);
+
+        self.mollusk.process_instruction(
+            &self.instruction(FundraiserInstructions::Checker { unwrap_sol }, metas),
+            &accounts
+        )
+    }
+
+    /// `refund` of `contributor` into a fresh token account.
+    fn refund(
+        &self,
+        contributor: &Pubkey,
+        contributor_account: (Pubkey, AccountSharedData),
+        fundraiser_account: AccountSharedData,
+        vault_amount: u64,
+        remaining: Vec<(AccountMeta, AccountSharedData)>
+    ) -> mollusk_svm::result::InstructionResult {
+        let contributor_ata = (Pubkey::new_unique(), self.token_account(self.token_state(contributor, 0)));
+
+        self.refund_with(contributor, contributor_account, contributor_ata, fundraiser_account, vault_amount, remaining)
+    }
+
+    /// `refund` of `contributor` into the given token account, the rent goes back to the
+    /// payer recorded on the contributor account.
+    fn refund_with(
+        &self,
+        contributor: &Pubkey,
+        contributor_account: (Pubkey, AccountSharedData),
+        contributor_ata: (Pubkey, AccountSharedData),
+        fundraiser_account: AccountSharedData,
+        vault_amount: u64,
+        remaining: Vec<(AccountMeta, AccountSharedData)>
+    ) -> mollusk_svm::result::InstructionResult {
+        let rent_payer = Contributor::load(contributor_account.1.data()).unwrap().rent_payer;
+
+        let mut metas = vec![
+            AccountMeta::new(*contributor, true),
+            AccountMeta::new(contributor_account.0, false),
+            AccountMeta::new(contributor_ata.0, false),
+            AccountMeta::new(self.fundraiser, false),
+            AccountMeta::new(self.vault, false),
+            AccountMeta::new_readonly(self.token_program, false),
+            AccountMeta::new_readonly(self.mint_to_raise, false),
+            AccountMeta::new(rent_payer, false),
+            AccountMeta::new_readonly(self.system_program, false),
+            AccountMeta::new_readonly(spl_associated_token_account::ID, false),
+        ];
+        let mut accounts = vec![
+            (*contributor, AccountSharedData::new(1_000_000_000, 0, &self.system_program)),
+            contributor_account,
+            contributor_ata,
+            (self.fundraiser, fundraiser_account),
+            (self.vault, self.token_account(self.token_state(&self.fundraiser, vault_amount))),
+            (self.token_program, self.token_program_account.clone()),
+            (self.mint_to_raise, self.mint_account()),
+            (self.system_program, self.system_program_account.clone()),
+            (spl_associated_token_account::ID, self.associated_token_program_account.clone()),
+        ];
+        if rent_payer != *contributor {
+            accounts.push((rent_payer, AccountSharedData::new(0, 0, &self.system_program)));
+        }
+        self.append_remaining(&mut metas, &mut accounts, remaining);
+
+        self.mollusk.process_instruction(
+            &self.instruction(FundraiserInstructions::Refund { unwrap_sol: false }, metas),
+            &accounts
+        )
+    }
+
+    /// `close_contributor` of `contributor`, handing the rent to `rent_payer`.
+    fn close_contributor(
+        &self,
+        contributor: &Pubkey,
+        contributor_account: (Pubkey, AccountSharedData),
+        fundraiser_account: AccountSharedData,
+        rent_payer: &Pubkey
+    ) -> mollusk_svm::result::InstructionResult {
+        let metas = vec![
+            AccountMeta::new_readonly(*contributor, false),
+            AccountMeta::new(contributor_account.0, false),
+            AccountMeta::new(self.fundraiser, false),
+            AccountMeta::new(*rent_payer, false),
+        ];
+        let mut accounts = vec![
+            (*contributor, AccountSharedData::new(0, 0, &self.system_program)),
+            contributor_account,
+            (self.fundraiser, fundraiser_account),
+        ];
+        if rent_payer != contributor {
+            accounts.push((*rent_payer, AccountSharedData::new(0, 0, &self.system_program)));
+        }
+
+        self.mollusk.process_instruction(
+            &self.instruction(FundraiserInstructions::CloseContributor, metas),
+            &accounts
+        )
+    }
+}
+
+/// Instructions sysvar of a transaction made of `instructions`, executing the last one.
+fn instructions_sysvar(instructions: &[Instruction]) -> AccountSharedData {
+    use solana_sdk::instruction::{BorrowedAccountMeta, BorrowedInstruction};
+
+    let borrowed: Vec<BorrowedInstruction> = instructions.iter().map(|instruction| BorrowedInstruction {
+        program_id: &instruction.program_id,
+        accounts: instruction.accounts.iter().map(|meta| BorrowedAccountMeta {
+            pubkey: &meta.pubkey,
+            is_signer: meta.is_signer,
+            is_writable: meta.is_writable,
+        }).collect(),
+        data: &instruction.data,
+    }).collect();
+
+    let mut data = solana_sdk::sysvar::instructions::construct_instructions_data(&borrowed);
+    let current_index = (instructions.len() as u16 - 1).to_le_bytes();
+    let len = data.len();
+    data[len - 2..].copy_from_slice(&current_index);
+
+    let mut account = AccountSharedData::new(0, data.len(), &solana_sdk::sysvar::ID);
+    account.set_data_from_slice(&data);
+
+    account
+}
+
+/// ed25519 program instruction carrying a signature of `message` by `signer`. The
+/// signature itself is checked by the runtime, the program only looks at what was signed.
+fn ed25519_instruction(signer: &Pubkey, message: &[u8]) -> Instruction {
+    const PUBLIC_KEY_OFFSET: u16 = 16;
+    const SIGNATURE_OFFSET: u16 = PUBLIC_KEY_OFFSET + 32;
+    const MESSAGE_OFFSET: u16 = SIGNATURE_OFFSET + 64;
+
+    let mut data = vec![1, 0];
+    for offset in [
+        SIGNATURE_OFFSET,
+        u16::MAX,
+        PUBLIC_KEY_OFFSET,
+        u16::MAX,
+        MESSAGE_OFFSET,
+        message.len() as u16,
+        u16::MAX,
+    ] {
+        data.extend_from_slice(&offset.to_le_bytes());
+    }
+    data.extend_from_slice(signer.as_ref());
+    data.extend_from_slice(&[0; 64]);
+    data.extend_from_slice(message);
+
+    Instruction::new_with_bytes(solana_sdk::ed25519_program::ID, &data, vec![])
+}
+
+fn token_amount(result: &mollusk_svm::result::InstructionResult, account: &Pubkey) -> u64 {
+    crate::token::unpack_account(result.get_account(account).unwrap().data()).unwrap().amount
+}
+
+fn fundraiser_state(result: &mollusk_svm::result::InstructionResult, fundraiser: &Pubkey) -> Fundraiser {
+    *Fundraiser::load(result.get_account(fundraiser).unwrap().data()).unwrap()
+}
+
+#[test]
+fn initialize() {
+
+    let program_id = pubkey!("22222222222222222222222222222222222222222222");
+
+    let mut mollusk = Mollusk::new(&program_id, "target/deploy/native_fundraiser");
+    mollusk_token::token::add_program(&mut mollusk);
+
+    let (
+        token_program, 
+        token_program_account
+    ) = mollusk_token::token::keyed_account();
+
+    let (
+        system_program, 
+        system_program_account
+    ) = program::keyed_account_for_system_program();
+
+    let maker = Pubkey::new_unique();
+    let mint_to_raise = Pubkey::new_unique();
+    let mut mint_to_raise_account = AccountSharedData::new(
+        mollusk.sysvars.rent.minimum_balance(spl_token::state::Mint::LEN), 
+        spl_token::state::Mint::LEN,
+        &token_program
+    );
+    solana_sdk::program_pack::Pack::pack(
+        spl_token::state::Mint {
+            mint_authority: COption::None,
+            supply: 100_000_000_000,
+            decimals: 6,
+            is_initialized: true,
+            freeze_authority: COption::None
+        },
+        mint_to_raise_account.data_as_mut_slice()
+    ).unwrap();
+
+    let (fundraiser, _bump) = Pubkey::find_program_address(
+        &[b"fundraiser", maker.as_ref()],
+        &program_id
+    );
+    let fundraiser_account = AccountSharedData::new(0, 0, &system_program);
+
+    let (vault, _) = Pubkey::find_program_address(
+        &[b"vault", fundraiser.as_ref()],
+        &program_id
+    );
+
+    let vault_account = AccountSharedData::new(
+        0,
+        0,
+        &system_program
+    );
+
+    let (config, config_bump) = Pubkey::find_program_address(
+        &[b"config"],
+        &program_id
+    );
+    let mut config_account = AccountSharedData::new(
+        mollusk.sysvars.rent.minimum_balance(Config::LEN),
+        Config::LEN,
+        &program_id
+    );
+    config_account.set_data_from_slice(&borsh::to_vec(&Config {
+        discriminator: AccountDiscriminator::Config as u8,
+        version: Config::VERSION,
+        admin: Pubkey::new_unique(),
+        paused: false,
+        fee_bps: 0,
+        fee_recipient: Pubkey::new_unique(),
+        max_duration: 0,
+        max_amount_to_raise: 0,
+        bump: config_bump,
+        rejected_extensions: 0,
+    }).unwrap());
+
+    let (mint_allowlist, mint_allowlist_bump) = Pubkey::find_program_address(
+        &[b"mint_allowlist"],
+        &program_id
+    );
+    let mut mint_allowlist_account = AccountSharedData::new(
+        mollusk.sysvars.rent.minimum_balance(MintAllowlist::LEN),
+        MintAllowlist::LEN,
+        &program_id
+    );
+    mint_allowlist_account.set_data_from_slice(&borsh::to_vec(&MintAllowlist {
+        discriminator: AccountDiscriminator::MintAllowlist as u8,
+        version: MintAllowlist::VERSION,
+        count: 1,
+        mints: core::array::from_fn(|i| if i == 0 { mint_to_raise } else { Pubkey::default() }),
+        bump: mint_allowlist_bump,
+    }).unwrap());
+
+    let (rent_pool, _) = Pubkey::find_program_address(
+        &[b"rent_pool", fundraiser.as_ref()],
+        &program_id
+    );
+
+    // the maker sponsors the rent of the first contributor accounts
+    let data = borsh::to_vec(&FundraiserInstructions::Initialize(InitializeArgs {
+        rent_pool_lamports: 100_000_000,
+        ..InitializeArgs::new(AMOUNT_TO_RAISE, TODAY_TIMESTAMP, DURATION_DAYS)
+    })).unwrap();
+
+    let instruction = Instruction::new_with_bytes(
+        program_id, 
+        &data, 
+        vec![
+            AccountMeta::new(maker, true),  // writable and signer
+            AccountMeta::new_readonly(mint_to_raise, false),  // readonly
+            AccountMeta::new(fundraiser, false),  // writable
+            AccountMeta::new(vault, false),  // writable
+            AccountMeta::new_readonly(system_program, false),  // readonly
+            AccountMeta::new_readonly(token_program, false),  // readonly
+            AccountMeta::new_readonly(config, false),  // readonly
+            AccountMeta::new_readonly(mint_allowlist, false),  // readonly
+            AccountMeta::new(rent_pool, false),  // writable
+            AccountMeta::new_readonly(spl_associated_token_account::ID, false),  // readonly, unused without `ata_vault`
+        ]
+    );
+
+    let result: mollusk_svm::result::InstructionResult = mollusk.process_instruction(
+        &instruction, 
+        &[
+            (
+                maker, 
+                AccountSharedData::new(1_000_000_000_000, 0, &Pubkey::default())
+            ),
+            (
+                mint_to_raise,
+                mint_to_raise_account
+            ),
+            (
+                fundraiser,
+                fundraiser_account
+            ),
+            (
+                vault,
+                vault_account
+            ),
+            (system_program, system_program_account),
+            (token_program, token_program_account),
+            (config, config_account),
+            (mint_allowlist, mint_allowlist_account),
+            (rent_pool, AccountSharedData::new(0, 0, &system_program)),
+            (spl_associated_token_account::ID, AccountSharedData::default())
+        ],
+    );
+    println!("vault: {}", vault);
+    assert!(!result.program_result.is_err(), "Program execution failed: {:?}", result.program_result);    }
+
+#[test]
+fn initialize_rejects_invalid_args() {
+    let fixture = Fixture::new();
+
+    // nothing to raise
+    let result = fixture.initialize(
+        InitializeArgs::new(0, TODAY_TIMESTAMP, DURATION_DAYS),
+        fixture.config_account(0, false),
+        vec![]
+    );
+    assert_eq!(result.program_result, ProgramResult::Failure(ProgramError::InvalidInstructionData));
+
+    // milestones without an arbiter or a quorum could never be released
+    let (milestones, _) = Pubkey::find_program_address(
+        &[b"milestones", fixture.fundraiser.as_ref()],
+        &fixture.program_id
+    );
+    let milestone_args = InitializeArgs {
+        milestone_shares: vec![50, 50],
+        ..InitializeArgs::new(AMOUNT_TO_RAISE, TODAY_TIMESTAMP, DURATION_DAYS)
+    };
+    let result = fixture.initialize(
+        milestone_args.clone(),
+        fixture.config_account(0, false),
+        vec![(AccountMeta::new(milestones, false), AccountSharedData::new(0, 0, &fixture.system_program))]
+    );
+    assert_eq!(result.program_result, ProgramResult::Failure(FundraiserError::InvalidArbiter.into()));
+
+    // either of them can approve the milestones
+    for args in [
+        InitializeArgs { arbiter: Pubkey::new_unique(), ..milestone_args.clone() },
+        InitializeArgs { quorum: 50, voting_period: 86400, ..milestone_args },
+    ] {
+        let result = fixture.initialize(
+            args,
+            fixture.config_account(0, false),
+            vec![(AccountMeta::new(milestones, false), AccountSharedData::new(0, 0, &fixture.system_program))]
+        );
+        assert!(!result.program_result.is_err(), "Program execution failed: {:?}", result.program_result);
+    }
+}
+
+#[test]
+fn initialize_within_config_limits() {
+    let fixture = Fixture::new();
+
+    let limited_config = |max_duration: u8, max_amount_to_raise: u64| {
+        let mut account = fixture.config_account(0, false);
+        let mut config: Config = borsh::from_slice(account.data()).unwrap();
+        config.max_duration = max_duration;
+        config.max_amount_to_raise = max_amount_to_raise;
+        account.set_data_from_slice(&borsh::to_vec(&config).unwrap());
+
+        account
+    };
+    let args = InitializeArgs::new(AMOUNT_TO_RAISE, TODAY_TIMESTAMP, DURATION_DAYS);
+
+    for config in [
+        limited_config(DURATION_DAYS - 1, 0),
+        limited_config(0, AMOUNT_TO_RAISE - 1),
+    ] {
+        let result = fixture.initialize(args.clone(), config, vec![]);
+        assert_eq!(result.program_result, ProgramResult::Failure(FundraiserError::ExceedsConfigLimit.into()));
+    }
+
+    // the limits themselves are allowed
+    let result = fixture.initialize(args, limited_config(DURATION_DAYS, AMOUNT_TO_RAISE), vec![]);
+    assert!(!result.program_result.is_err(), "Program execution failed: {:?}", result.program_result);
+}
+
+#[test]
+fn mint_allowlist() {
+    use crate::constants::MAX_ALLOWED_MINTS;
+
+    let fixture = Fixture::new();
+    let config_account = fixture.config_account(0, false);
+    let admin = borsh::from_slice::<Config>(config_account.data()).unwrap().admin;
+    let (mint_allowlist, _) = fixture.mint_allowlist_account(&[]);
+
+    let update = |instruction: FundraiserInstructions, signer: &Pubkey, allowlist_account: AccountSharedData| {
+        let mut metas = vec![
+            AccountMeta::new(*signer, true),
+            AccountMeta::new_readonly(fixture.config, false),
+            AccountMeta::new(mint_allowlist, false),
+        ];
+        if matches!(instruction, FundraiserInstructions::AddAllowedMint { .. }) {
+            metas.push(AccountMeta::new_readonly(fixture.system_program, false));
+        }
+
+        fixture.mollusk.process_instruction(
+            &fixture.instruction(instruction, metas),
+            &[
+                (*signer, AccountSharedData::new(1_000_000_000, 0, &fixture.system_program)),
+                (fixture.config, config_account.clone()),
+                (mint_allowlist, allowlist_account),
+                (fixture.system_program, fixture.system_program_account.clone()),
+            ]
+        )
+    };
+    let allowed = |result: &mollusk_svm::result::InstructionResult| {
+        borsh::from_slice::<MintAllowlist>(result.get_account(&mint_allowlist).unwrap().data()).unwrap()
+    };
+    let add = FundraiserInstructions::AddAllowedMint { mint: fixture.mint_to_raise };
+    let remove = FundraiserInstructions::RemoveAllowedMint { mint: fixture.mint_to_raise };
+
+    // only the admin manages the allowlist
+    let result = update(add.clone(), &Pubkey::new_unique(), AccountSharedData::new(0, 0, &fixture.system_program));
+    assert_eq!(result.program_result, ProgramResult::Failure(FundraiserError::InvalidAdmin.into()));
+
+    // the first mint creates the allowlist
+    let result = update(add.clone(), &admin, AccountSharedData::new(0, 0, &fixture.system_program));
+    assert!(!result.program_result.is_err(), "Program execution failed: {:?}", result.program_result);
+    assert!(allowed(&result).contains(&fixture.mint_to_raise));
+    assert_eq!(allowed(&result).count, 1);
+
+    // adding it again changes nothing
+    let allowlist_account = result.get_account(&mint_allowlist).unwrap().clone();
+    let result = update(add.clone(), &admin, allowlist_account.clone());
+    assert!(!result.program_result.is_err(), "Program execution failed: {:?}", result.program_result);
+    assert_eq!(allowed(&result).count, 1);
+
+    let result = update(remove.clone(), &Pubkey::new_unique(), allowlist_account.clone());
+    assert_eq!(result.program_result, ProgramResult::Failure(FundraiserError::InvalidAdmin.into()));
+
+    let result = update(remove.clone(), &admin, allowlist_account);
+    assert!(!result.program_result.is_err(), "Program execution failed: {:?}", result.program_result);
+    assert!(!allowed(&result).contains(&fixture.mint_to_raise));
+    assert_eq!(allowed(&result).count, 0);
+
+    // a mint that is not allowed cannot be removed
+    let allowlist_account = result.get_account(&mint_allowlist).unwrap().clone();
+    let result = update(remove, &admin, allowlist_account);
+    assert_eq!(result.program_result, ProgramResult::Failure(FundraiserError::MintNotAllowed.into()));
+
+    // nor added once the allowlist is full
+    let mints: Vec<Pubkey> = (0..MAX_ALLOWED_MINTS).map(|_| Pubkey::new_unique()).collect();
+    let result = update(add, &admin, fixture.mint_allowlist_account(&mints).1);
+    assert_eq!(result.program_result, ProgramResult::Failure(FundraiserError::MintAllowlistFull.into()));
+
+    // and fundraisers can only raise allowed mints
+    let result = fixture.initialize_with(
+        InitializeArgs::new(AMOUNT_TO_RAISE, TODAY_TIMESTAMP, DURATION_DAYS),
+        fixture.config_account(0, false),
+        fixture.mint_allowlist_account(&mints),
+        vec![]
+    );
+    assert_eq!(result.program_result, ProgramResult::Failure(FundraiserError::MintNotAllowed.into()));
+}
+
+#[test]
+fn init_config_by_upgrade_authority() {
+    use solana_sdk::bpf_loader_upgradeable;
+
+    let fixture = Fixture::new();
+    let authority = Pubkey::new_unique();
+    let (program_data, _) = Pubkey::find_program_address(
+        &[fixture.program_id.as_ref()],
+        &bpf_loader_upgradeable::ID
+    );
+
+    // `ProgramData` tag, deployment slot and upgrade authority, followed by the program
+    let mut data = vec![3, 0, 0, 0];
+    data.extend_from_slice(&42u64.to_le_bytes());
+    data.push(1);
+    data.extend_from_slice(authority.as_ref());
+    data.resize(data.len() + 64, 0);
+    let mut program_data_account = AccountSharedData::new(1_000_000_000, data.len(), &bpf_loader_upgradeable::ID);
+    program_data_account.set_data_from_slice(&data);
+
+    let args = ConfigArgs {
+        admin: Pubkey::new_unique(),
+        paused: false,
+        fee_bps: 250,
+        fee_recipient: fixture.fee_recipient,
+        max_duration: 30,
+        max_amount_to_raise: 0,
+        rejected_extensions: 0,
+    };
+
+    let init_config = |signer: &Pubkey, program_data: &Pubkey| fixture.mollusk.process_instruction(
+        &fixture.instruction(FundraiserInstructions::InitConfig(args.clone()), vec![
+            AccountMeta::new(*signer, true),
+            AccountMeta::new(fixture.config, false),
+            AccountMeta::new_readonly(*program_data, false),
+            AccountMeta::new_readonly(fixture.system_program, false),
+        ]),
+        &[
+            (*signer, AccountSharedData::new(1_000_000_000, 0, &fixture.system_program)),
+            (fixture.config, AccountSharedData::new(0, 0, &fixture.system_program)),
+            (*program_data, program_data_account.clone()),
+            (fixture.system_program, fixture.system_program_account.clone()),
+        ]
+    );
+
+    // only the upgrade authority can claim the config
+    let result = init_config(&Pubkey::new_unique(), &program_data);
+    assert_eq!(result.program_result, ProgramResult::Failure(FundraiserError::InvalidAdmin.into()));
+
+    // of this program
+    let result = init_config(&authority, &Pubkey::new_unique());
+    assert_eq!(result.program_result, ProgramResult::Failure(ProgramError::InvalidSeeds));
+
+    let result = init_config(&authority, &program_data);
+    assert!(!result.program_result.is_err(), "Program execution failed: {:?}", result.program_result);
+
+    let config: Config = borsh::from_slice(result.get_account(&fixture.config).unwrap().data()).unwrap();
+    assert_eq!(config.admin, args.admin);
+    assert_eq!(config.fee_bps, args.fee_bps);
+    assert_eq!(config.max_duration, args.max_duration);
+    assert_eq!(config.bump, fixture.config_bump);
+}
+
+#[test]
+fn update_config_by_admin() {
+    let fixture = Fixture::new();
+    let config_account = fixture.config_account(0, false);
+    let admin = borsh::from_slice::<Config>(config_account.data()).unwrap().admin;
+
+    let args = ConfigArgs {
+        admin,
+        paused: false,
+        fee_bps: 500,
+        fee_recipient: fixture.fee_recipient,
+        max_duration: 0,
+        max_amount_to_raise: AMOUNT_TO_RAISE,
+        rejected_extensions: 0,
+    };
+
+    let update_config = |signer: &Pubkey, is_signer: bool| fixture.mollusk.process_instruction(
+        &fixture.instruction(FundraiserInstructions::UpdateConfig(args.clone()), vec![
+            AccountMeta::new_readonly(*signer, is_signer),
+            AccountMeta::new(fixture.config, false),
+        ]),
+        &[
+            (*signer, AccountSharedData::new(1_000_000_000, 0, &fixture.system_program)),
+            (fixture.config, config_account.clone()),
+        ]
+    );
+
+    let result = update_config(&admin, false);
+    assert_eq!(result.program_result, ProgramResult::Failure(ProgramError::MissingRequiredSignature));
+
+    let result = update_config(&Pubkey::new_unique(), true);
+    assert_eq!(result.program_result, ProgramResult::Failure(FundraiserError::InvalidAdmin.into()));
+
+    let result = update_config(&admin, true);
+    assert!(!result.program_result.is_err(), "Program execution failed: {:?}", result.program_result);
+
+    let config: Config = borsh::from_slice(result.get_account(&fixture.config).unwrap().data()).unwrap();
+    assert_eq!(config.fee_bps, 500);
+    assert_eq!(config.max_amount_to_raise, AMOUNT_TO_RAISE);
+}
+
+#[test]
+fn malformed_instruction_data() {
+    let fixture = Fixture::new();
+
+    let contribute = borsh::to_vec(&FundraiserInstructions::Contribute {
+        amount: 1_000_000,
+        intent: None,
+        wrap_sol: false,
+    }).unwrap();
+    let mut trailing = contribute.clone();
+    trailing.push(0);
+
+    // empty, truncated, an unknown variant or left over bytes
+    for data in [vec![], contribute[..contribute.len() - 1].to_vec(), vec![u8::MAX], trailing] {
+        let result = fixture.mollusk.process_instruction(
+            &Instruction::new_with_bytes(fixture.program_id, &data, vec![]),
+            &[]
+        );
+        assert_eq!(result.program_result, ProgramResult::Failure(ProgramError::InvalidInstructionData));
+    }
+}
+
+#[test]
+fn get_status() {
+    use crate::{
+        instructions::get_status::FundraiserStatus,
+        state::fundraiser::{Fundraiser, FundraiserPhase}
+    };
+
+    let program_id = pubkey!("22222222222222222222222222222222222222222222");
+
+    let mollusk = Mollusk::new(&program_id, "target/deploy/native_fundraiser");
+
+    let fundraiser = Pubkey::new_unique();
+
+    // ended at the mollusk clock, with the whole amount raised
+    let mut fundraiser_data = vec![0; Fundraiser::LEN];
+    let fundraiser_state = Fundraiser::new_mut(&mut fundraiser_data).unwrap();
+    fundraiser_state.set_amount_to_raise(AMOUNT_TO_RAISE);
+    fundraiser_state.set_current_amount(AMOUNT_TO_RAISE);
+    fundraiser_state.set_contributor_count(3);
+    fundraiser_state.min_success_percentage = 100;
+
+    let mut fundraiser_account = AccountSharedData::new(
+        mollusk.sysvars.rent.minimum_balance(Fundraiser::LEN),
+        Fundraiser::LEN,
+        &program_id
+    );
+    fundraiser_account.set_data_from_slice(&fundraiser_data);
+
+    let instruction = Instruction::new_with_bytes(
+        program_id, 
+        &borsh::to_vec(&FundraiserInstructions::GetStatus).unwrap(), 
+        vec![AccountMeta::new_readonly(fundraiser, false)]
+    );
+
+    let result = mollusk.process_instruction(
+        &instruction, 
+        &[(fundraiser, fundraiser_account)]
+    );
+    assert!(!result.program_result.is_err(), "Program execution failed: {:?}", result.program_result);
+
+    let status: FundraiserStatus = borsh::from_slice(&result.return_data).unwrap();
+    assert_eq!(status, FundraiserStatus {
+        current_amount: AMOUNT_TO_RAISE,
+        amount_to_raise: AMOUNT_TO_RAISE,
+        deadline: 0,
+        phase: FundraiserPhase::Succeeded,
+        contributor_count: 3,
+    });
+}
+
+#[test]
+fn lifecycle_transitions() {
+    use crate::{
+        error::FundraiserError,
+        state::fundraiser::{Fundraiser, LifecycleStatus::{self, *}}
+    };
+
+    let allowed = [
+        (Pending, Active),
+        (Pending, Cancelled),
+        (Active, Succeeded),
+        (Active, Failed),
+        (Active, Cancelled),
+        (Succeeded, Settled),
+        (Succeeded, Failed),
+        (Succeeded, Cancelled),
+        (Settled, Cancelled),
+    ];
+
+    // the allowed moves leading to each status from a new fundraiser
+    let path_to = |status: LifecycleStatus| match status {
+        Pending => vec![],
+        Active => vec![Active],
+        Succeeded => vec![Active, Succeeded],
+        Failed => vec![Active, Failed],
+        Cancelled => vec![Cancelled],
+        Settled => vec![Active, Succeeded, Settled],
+    };
+
+    for from in LifecycleStatus::ALL {
+        for to in LifecycleStatus::ALL {
+            let expected = allowed.contains(&(from, to));
+            assert_eq!(from.can_transition_to(to), expected, "{:?} -> {:?}", from, to);
+
+            let mut data = vec![0; Fundraiser::LEN];
+            let fundraiser = Fundraiser::new_mut(&mut data).unwrap();
+            for status in path_to(from) {
+                fundraiser.transition(status).unwrap();
+            }
+            assert_eq!(fundraiser.status().unwrap(), from);
+
+            let result = fundraiser.transition(to);
+            if expected {
+                assert!(result.is_ok(), "{:?} -> {:?}", from, to);
+                assert_eq!(fundraiser.status().unwrap(), to);
+            } else {
+                assert_eq!(result, Err(FundraiserError::InvalidStatusTransition.into()), "{:?} -> {:?}", from, to);
+                assert_eq!(fundraiser.status().unwrap(), from);
+            }
+        }
+    }
+
+    // a corrupted status is rejected rather than read out of bounds
+    assert_eq!(LifecycleStatus::try_from(LifecycleStatus::ALL.len() as u8), Err(ProgramError::InvalidAccountData));
+}
+
+#[test]
+fn lifecycle_follows_the_clock() {
+    use crate::state::fundraiser::{Fundraiser, LifecycleStatus};
+
+    const DAY: i64 = 86400;
+
+    let new_fundraiser = |current_amount: u64| {
+        let mut data = vec![0; Fundraiser::LEN];
+        let fundraiser = Fundraiser::new_mut(&mut data).unwrap();
+        fundraiser.set_amount_to_raise(AMOUNT_TO_RAISE);
+        fundraiser.set_current_amount(current_amount);
+        fundraiser.set_time_started(TODAY_TIMESTAMP);
+        fundraiser.duration = DURATION_DAYS;
+        fundraiser.min_success_percentage = 100;
+        *fundraiser
+    };
+
+    let deadline = TODAY_TIMESTAMP + DURATION_DAYS as i64 * DAY;
+
+    let mut fundraiser = new_fundraiser(AMOUNT_TO_RAISE);
+    assert_eq!(fundraiser.update_status(TODAY_TIMESTAMP - 1), Ok(LifecycleStatus::Pending));
+    assert_eq!(fundraiser.update_status(TODAY_TIMESTAMP), Ok(LifecycleStatus::Active));
+    assert_eq!(fundraiser.update_status(deadline - 1), Ok(LifecycleStatus::Active));
+    assert_eq!(fundraiser.update_status(deadline), Ok(LifecycleStatus::Succeeded));
+    assert_eq!(fundraiser.update_status(deadline + DAY), Ok(LifecycleStatus::Succeeded));
+
+    // a fundraiser created late goes through every status it skipped
+    let mut fundraiser = new_fundraiser(AMOUNT_TO_RAISE - 1);
+    assert_eq!(fundraiser.update_status(deadline), Ok(LifecycleStatus::Failed));
+
+    // contributions are only taken while active
+    let mut fundraiser = new_fundraiser(0);
+    assert!(fundraiser.require_active().is_err());
+    fundraiser.update_status(TODAY_TIMESTAMP).unwrap();
+    assert!(fundraiser.require_active().is_ok());
+    fundraiser.update_status(deadline).unwrap();
+    assert!(fundraiser.require_active().is_err());
+
+    // cancellation is final
+    let mut fundraiser = new_fundraiser(AMOUNT_TO_RAISE);
+    fundraiser.transition(LifecycleStatus::Cancelled).unwrap();
+    assert_eq!(fundraiser.update_status(deadline), Ok(LifecycleStatus::Cancelled));
+}
+
+/// Pledges of 6 and 4 tokens, the second one collectable only if `approved`.
+fn collect_pledges(fixture: &Fixture, approved: bool) -> (
+    mollusk_svm::result::InstructionResult,
+    [(Pubkey, Pubkey); 2]
+) {
+    let args = InitializeArgs {
+        pledge_mode: true,
+        ..InitializeArgs::new(AMOUNT_TO_RAISE, TODAY_TIMESTAMP, DURATION_DAYS)
+    };
+    let fundraiser_account = fixture.fundraiser_account(&args, 0, |fundraiser| {
+        fundraiser.set_current_amount(AMOUNT_TO_RAISE);
+        fundraiser.set_contributor_count(2);
+        fundraiser.set_pending_pledges(2);
+    });
+
+    let mut metas = vec![
+        AccountMeta::new(fixture.maker, true),
+        AccountMeta::new(fixture.fundraiser, false),
+        AccountMeta::new(fixture.vault, false),
+        AccountMeta::new_readonly(fixture.token_program, false),
+        AccountMeta::new_readonly(fixture.mint_to_raise, false),
+        AccountMeta::new_readonly(fixture.config, false),
+    ];
+    let mut accounts = vec![
+        (fixture.maker, AccountSharedData::new(1_000_000_000, 0, &fixture.system_program)),
+        (fixture.fundraiser, fundraiser_account),
+        (fixture.vault, fixture.token_account(fixture.token_state(&fixture.fundraiser, 0))),
+        (fixture.token_program, fixture.token_program_account.clone()),
+        (fixture.mint_to_raise, fixture.mint_account()),
+        (fixture.config, fixture.config_account(0, fixture.config_paused)),
+    ];
+
+    let pledges = [(6_000_000, true), (4_000_000, approved)].map(|(amount, approved)| {
+        let contributor = Pubkey::new_unique();
+        let contributor_ata = Pubkey::new_unique();
+        let (contributor_account, account) = fixture.contributor_account(&contributor, amount, PledgeStatus::Pending);
+
+        let mut token_state = fixture.token_state(&contributor, amount);
+        if approved {
+            token_state.delegate = COption::Some(fixture.fundraiser);
+            token_state.delegated_amount = amount;
+        }
+
+        metas.push(AccountMeta::new(contributor_account, false));
+        metas.push(AccountMeta::new(contributor_ata, false));
+        accounts.push((contributor_account, account));
+        accounts.push((contributor_ata, fixture.token_account(token_state)));
+
+        (contributor, contributor_account)
+    });
+
+    let result = fixture.mollusk.process_instruction(
+        &fixture.instruction(FundraiserInstructions::Collect, metas),
+        &accounts
+    );
+
+    (result, pledges)
+}
+
+#[test]
+fn collect_then_payout() {
+    let mut fixture = Fixture::new();
+    fixture.mollusk.sysvars.clock.unix_timestamp = DEADLINE;
+
+    let (result, pledges) = collect_pledges(&fixture, true);
+    assert!(!result.program_result.is_err(), "Program execution failed: {:?}", result.program_result);
+
+    assert_eq!(token_amount(&result, &fixture.vault), AMOUNT_TO_RAISE);
+
+    let fundraiser = fundraiser_state(&result, &fixture.fundraiser);
+    assert_eq!(fundraiser.status().unwrap(), LifecycleStatus::Succeeded);
+    assert_eq!(fundraiser.current_amount(), AMOUNT_TO_RAISE);
+    assert_eq!(fundraiser.pending_pledges(), 0);
+
+    for (_, contributor_account) in pledges {
+        let data = result.get_account(&contributor_account).unwrap().data();
+        assert_eq!(Contributor::load(data).unwrap().pledge_status(), PledgeStatus::Collected);
+    }
+
+    // the maker is paid once every pledge is collected
+    let fundraiser_account = result.get_account(&fixture.fundraiser).unwrap().clone();
+    let result = fixture.checker(fundraiser_account, AMOUNT_TO_RAISE, 0, vec![]);
+    assert!(!result.program_result.is_err(), "Program execution failed: {:?}", result.program_result);
+}
+
+#[test]
+fn checker_waits_for_pledges() {
+    let mut fixture = Fixture::new();
+    fixture.mollusk.sysvars.clock.unix_timestamp = DEADLINE;
+
+    let args = InitializeArgs {
+        pledge_mode: true,
+        ..InitializeArgs::new(AMOUNT_TO_RAISE, TODAY_TIMESTAMP, DURATION_DAYS)
+    };
+    let fundraiser_account = fixture.fundraiser_account(&args, 0, |fundraiser| {
+        fundraiser.set_current_amount(AMOUNT_TO_RAISE);
+        fundraiser.set_pending_pledges(1);
+    });
+
+    let result = fixture.checker(fundraiser_account, 0, 0, vec![]);
+    assert_eq!(result.program_result, ProgramResult::Failure(FundraiserError::PledgesNotCollected.into()));
+}
+
+#[test]
+fn collect_waits_while_paused() {
+    let mut fixture = Fixture::new();
+    fixture.mollusk.sysvars.clock.unix_timestamp = DEADLINE;
+    fixture.config_paused = true;
+
+    let (result, _) = collect_pledges(&fixture, true);
+    assert_eq!(result.program_result, ProgramResult::Failure(FundraiserError::Paused.into()));
+
+    fixture.config_paused = false;
+
+    let (result, _) = collect_pledges(&fixture, true);
+    assert!(!result.program_result.is_err(), "Program execution failed: {:?}", result.program_result);
+}
+
+#[test]
+fn collect_failed_pledge_then_refund() {
+    let mut fixture = Fixture::new();
+    fixture.mollusk.sysvars.clock.unix_timestamp = DEADLINE;
+
+    let (result, [(collected, collected_account), (failed, failed_account)]) = collect_pledges(&fixture, false);
+    assert!(!result.program_result.is_err(), "Program execution failed: {:?}", result.program_result);
+
+    // the collected pledge alone is below the threshold, the fundraiser fails
+    assert_eq!(token_amount(&result, &fixture.vault), 6_000_000);
+
+    let fundraiser = fundraiser_state(&result, &fixture.fundraiser);
+    assert_eq!(fundraiser.status().unwrap(), LifecycleStatus::Failed);
+    assert_eq!(fundraiser.current_amount(), 6_000_000);
+    assert_eq!(fundraiser.pending_pledges(), 0);
+
+    let data = result.get_account(&failed_account).unwrap().data();
+    assert_eq!(Contributor::load(data).unwrap().pledge_status(), PledgeStatus::Failed);
+
+    let fundraiser_account = result.get_account(&fixture.fundraiser).unwrap().clone();
+    let checker_result = fixture.checker(fundraiser_account.clone(), 6_000_000, 0, vec![]);
+    assert!(checker_result.program_result.is_err());
+
+    // the collected pledge goes back to its contributor
+    let refund_result = fixture.refund(
+        &collected,
+        (collected_account, result.get_account(&collected_account).unwrap().clone()),
+        fundraiser_account.clone(),
+        6_000_000,
+        vec![]
+    );
+    assert!(!refund_result.program_result.is_err(), "Program execution failed: {:?}", refund_result.program_result);
+    assert_eq!(token_amount(&refund_result, &fixture.vault), 0);
+    assert_eq!(fundraiser_state(&refund_result, &fixture.fundraiser).current_amount(), 0);
+
+    // the failed pledge never left its token account, only the contributor account is closed
+    let refund_result = fixture.refund(
+        &failed,
+        (failed_account, result.get_account(&failed_account).unwrap().clone()),
+        fundraiser_account,
+        6_000_000,
+        vec![]
+    );
+    assert!(!refund_result.program_result.is_err(), "Program execution failed: {:?}", refund_result.program_result);
+    assert_eq!(token_amount(&refund_result, &fixture.vault), 6_000_000);
+    assert_eq!(fundraiser_state(&refund_result, &fixture.fundraiser).current_amount(), 6_000_000);
+}
+
+#[test]
+fn refund_revokes_uncollected_pledge() {
+    let mut fixture = Fixture::new();
+    fixture.mollusk.sysvars.clock.unix_timestamp = DEADLINE;
+
+    const PLEDGE: u64 = 4_000_000;
+
+    // the fundraiser failed before its pledges were collected
+    let args = InitializeArgs {
+        pledge_mode: true,
+        ..InitializeArgs::new(AMOUNT_TO_RAISE, TODAY_TIMESTAMP, DURATION_DAYS)
+    };
+    let fundraiser_account = fixture.fundraiser_account(&args, 0, |fundraiser| {
+        fundraiser.set_current_amount(PLEDGE);
+        fundraiser.set_contributor_count(1);
+        fundraiser.set_pending_pledges(1);
+    });
+
+    let contributor = Pubkey::new_unique();
+    let contributor_ata = Pubkey::new_unique();
+    let mut token_state = fixture.token_state(&contributor, PLEDGE);
+    token_state.delegate = COption::Some(fixture.fundraiser);
+    token_state.delegated_amount = PLEDGE;
+
+    let result = fixture.refund_with(
+        &contributor,
+        fixture.contributor_account(&contributor, PLEDGE, PledgeStatus::Pending),
+        (contributor_ata, fixture.token_account(token_state)),
+        fundraiser_account,
+        0,
+        vec![]
+    );
+    assert!(!result.program_result.is_err(), "Program execution failed: {:?}", result.program_result);
+
+    // the tokens never moved, the fundraiser can no longer pull them
+    let token_account = crate::token::unpack_account(result.get_account(&contributor_ata).unwrap().data()).unwrap();
+    assert_eq!(token_account.amount, PLEDGE);
+    assert_eq!(token_account.delegate, COption::None);
+    assert_eq!(token_account.delegated_amount, 0);
+
+    let fundraiser = fundraiser_state(&result, &fixture.fundraiser);
+    assert_eq!(fundraiser.current_amount(), 0);
+    assert_eq!(fundraiser.contributor_count(), 0);
+}
+
+#[test]
+fn abandoned_milestones_refund_after_fee() {
+    let mut fixture = Fixture::new();
+    fixture.mollusk.sysvars.clock.unix_timestamp = DEADLINE;
+
+    const FEE_BPS: u16 = 500;
+    let contributions = [3_000_000, 3_000_000, 4_000_000];
+
+    let args = InitializeArgs {
+        milestone_shares: vec![50, 50],
+        ..InitializeArgs::new(AMOUNT_TO_RAISE, TODAY_TIMESTAMP, DURATION_DAYS)
+    };
+    let fundraiser_account = fixture.fundraiser_account(&args, FEE_BPS, |fundraiser| {
+        fundraiser.set_current_amount(AMOUNT_TO_RAISE);
+        fundraiser.set_contributor_count(contributions.len() as u64);
+    });
+    let (milestones, milestones_account) = fixture.milestones_account(&args.milestone_shares);
+
+    let result = fixture.checker(
+        fundraiser_account,
+        AMOUNT_TO_RAISE,
+        FEE_BPS,
+        vec![(AccountMeta::new(milestones, false), milestones_account)]
+    );
+    assert!(!result.program_result.is_err(), "Program execution failed: {:?}", result.program_result);
+
+    let mut vault_amount = token_amount(&result, &fixture.vault);
+    assert_eq!(vault_amount, AMOUNT_TO_RAISE - Config::fee(FEE_BPS, AMOUNT_TO_RAISE));
+
+    let milestones_data: Milestones = borsh::from_slice(result.get_account(&milestones).unwrap().data()).unwrap();
+    assert_eq!(milestones_data.total_raised, vault_amount);
+    assert_eq!(milestones_data.total_contributed, AMOUNT_TO_RAISE);
+
+    let mut fundraiser_account = result.get_account(&fixture.fundraiser).unwrap().clone();
+
+    let abandon_result = fixture.mollusk.process_instruction(
+        &fixture.instruction(FundraiserInstructions::AbandonMilestones, vec![
+            AccountMeta::new(fixture.maker, true),
+            AccountMeta::new(fixture.fundraiser, false),
+            AccountMeta::new(milestones, false),
+        ]),
+        &[
+            (fixture.maker, AccountSharedData::new(1_000_000_000, 0, &fixture.system_program)),
+            (fixture.fundraiser, fundraiser_account.clone()),
+            (milestones, result.get_account(&milestones).unwrap().clone()),
+        ]
+    );
+    assert!(!abandon_result.program_result.is_err(), "Program execution failed: {:?}", abandon_result.program_result);
+    let milestones_account = abandon_result.get_account(&milestones).unwrap().clone();
+
+    // every contributor gets their share of what is left after the fee, which adds up
+    // to the vault balance
+    for amount in contributions {
+        let contributor = Pubkey::new_unique();
+        let contributor_account = fixture.contributor_account(&contributor, amount, PledgeStatus::Pending);
+
+        let refund_result = fixture.refund(
+            &contributor,
+            contributor_account,
+            fundraiser_account,
+            vault_amount,
+            vec![(AccountMeta::new_readonly(milestones, false), milestones_account.clone())]
+        );
+        assert!(!refund_result.program_result.is_err(), "Program execution failed: {:?}", refund_result.program_result);
+
+        let refunded = vault_amount - token_amount(&refund_result, &fixture.vault);
+        assert_eq!(refunded, amount - Config::fee(FEE_BPS, amount));
+
+        vault_amount -= refunded;
+        fundraiser_account = refund_result.get_account(&fixture.fundraiser).unwrap().clone();
+    }
+
+    assert_eq!(vault_amount, 0);
+}
+
+#[test]
+fn flexible_withdrawals() {
+    use crate::state::fundraiser::FundingMode;
+
+    let mut fixture = Fixture::new();
+    fixture.mollusk.sysvars.clock.unix_timestamp = TODAY_TIMESTAMP + 60;
+
+    let args = InitializeArgs {
+        funding_mode: FundingMode::Flexible,
+        ..InitializeArgs::new(AMOUNT_TO_RAISE, TODAY_TIMESTAMP, DURATION_DAYS)
+    };
+    let fundraiser_account = fixture.fundraiser_account(&args, 0, |fundraiser| {
+        fundraiser.set_current_amount(AMOUNT_TO_RAISE / 4);
+    });
+
+    // far from the goal, the maker can still withdraw what came in so far
+    let result = fixture.checker(fundraiser_account, AMOUNT_TO_RAISE / 4, 0, vec![]);
+    assert!(!result.program_result.is_err(), "Program execution failed: {:?}", result.program_result);
+    assert_eq!(token_amount(&result, &fixture.vault), 0);
+
+    // the fundraiser keeps running
+    assert_eq!(fundraiser_state(&result, &fixture.fundraiser).status().unwrap(), LifecycleStatus::Active);
+
+    // contributors cannot take back what the maker may already have spent
+    let contributor = Pubkey::new_unique();
+    let fundraiser_account = result.get_account(&fixture.fundraiser).unwrap().clone();
+    let result = fixture.refund(
+        &contributor,
+        fixture.contributor_account(&contributor, AMOUNT_TO_RAISE / 4, PledgeStatus::Pending),
+        fundraiser_account.clone(),
+        0,
+        vec![]
+    );
+    assert_eq!(result.program_result, ProgramResult::Failure(FundraiserError::RefundsDisabled.into()));
+
+    // once the deadline passes, the last withdrawal closes the fundraiser
+    fixture.mollusk.sysvars.clock.unix_timestamp = DEADLINE;
+
+    let result = fixture.checker(fundraiser_account, AMOUNT_TO_RAISE / 10, 0, vec![]);
+    assert!(!result.program_result.is_err(), "Program execution failed: {:?}", result.program_result);
+
+    for account in [fixture.vault, fixture.fundraiser] {
+        assert_eq!(result.get_account(&account).unwrap().lamports(), 0);
+    }
+}
+
+#[test]
+fn success_threshold() {
+    let mut fixture = Fixture::new();
+    fixture.mollusk.sysvars.clock.unix_timestamp = DEADLINE;
+
+    let args = InitializeArgs {
+        min_success_percentage: 80,
+        ..InitializeArgs::new(AMOUNT_TO_RAISE, TODAY_TIMESTAMP, DURATION_DAYS)
+    };
+    let threshold = AMOUNT_TO_RAISE * 80 / 100;
+
+    // just short of the threshold the fundraiser fails and contributors get refunded
+    let fundraiser_account = fixture.fundraiser_account(&args, 0, |fundraiser| {
+        fundraiser.set_current_amount(threshold - 1);
+        fundraiser.set_contributor_count(1);
+    });
+
+    let result = fixture.checker(fundraiser_account.clone(), threshold - 1, 0, vec![]);
+    assert_eq!(result.program_result, ProgramResult::Failure(FundraiserError::BelowSuccessThreshold.into()));
+
+    let contributor = Pubkey::new_unique();
+    let result = fixture.refund(
+        &contributor,
+        fixture.contributor_account(&contributor, threshold - 1, PledgeStatus::Pending),
+        fundraiser_account,
+        threshold - 1,
+        vec![]
+    );
+    assert!(!result.program_result.is_err(), "Program execution failed: {:?}", result.program_result);
+    assert_eq!(token_amount(&result, &fixture.vault), 0);
+    assert_eq!(fundraiser_state(&result, &fixture.fundraiser).status().unwrap(), LifecycleStatus::Failed);
+
+    // reaching the threshold is enough even though the target was not met
+    let fundraiser_account = fixture.fundraiser_account(&args, 0, |fundraiser| {
+        fundraiser.set_current_amount(threshold);
+    });
+
+    let result = fixture.checker(fundraiser_account, threshold, 0, vec![]);
+    assert!(!result.program_result.is_err(), "Program execution failed: {:?}", result.program_result);
+    assert_eq!(result.get_account(&fixture.fundraiser).unwrap().lamports(), 0);
+}
+
+#[test]
+fn pause_blocks_contributions_and_payouts() {
+    let mut fixture = Fixture::new();
+    fixture.mollusk.sysvars.clock.unix_timestamp = TODAY_TIMESTAMP + 60;
+
+    let args = InitializeArgs::new(AMOUNT_TO_RAISE, TODAY_TIMESTAMP, DURATION_DAYS);
+    let fundraiser_account = fixture.fundraiser_account(&args, 0, |fundraiser| {
+        fundraiser.set_current_amount(AMOUNT_TO_RAISE);
+    });
+
+    let contributor = Pubkey::new_unique();
+    let contribute = |fixture: &Fixture, fundraiser_account: AccountSharedData| fixture.contribute(
+        FundraiserInstructions::Contribute { amount: 1_000_000, intent: None, wrap_sol: false },
+        &contributor,
+        AccountSharedData::new(0, 0, &fixture.system_program),
+        fixture.token_state(&contributor, 1_000_000),
+        fundraiser_account,
+        None
+    );
+
+    // the admin pauses the whole program
+    fixture.config_paused = true;
+
+    let result = contribute(&fixture, fundraiser_account.clone());
+    assert_eq!(result.program_result, ProgramResult::Failure(FundraiserError::Paused.into()));
+
+    let result = fixture.checker(fundraiser_account.clone(), AMOUNT_TO_RAISE, 0, vec![]);
+    assert_eq!(result.program_result, ProgramResult::Failure(FundraiserError::Paused.into()));
+
+    fixture.config_paused = false;
+
+    // the maker pauses their own fundraiser
+    let set_paused = |fundraiser_account: AccountSharedData, paused: bool| {
+        let result = fixture.mollusk.process_instruction(
+            &fixture.instruction(FundraiserInstructions::SetPaused { paused }, vec![
+                AccountMeta::new_readonly(fixture.maker, true),
+                AccountMeta::new(fixture.fundraiser, false),
+            ]),
+            &[
+                (fixture.maker, AccountSharedData::new(1_000_000_000, 0, &fixture.system_program)),
+                (fixture.fundraiser, fundraiser_account),
+            ]
+        );
+        assert!(!result.program_result.is_err(), "Program execution failed: {:?}", result.program_result);
+        assert_eq!(fundraiser_state(&result, &fixture.fundraiser).paused(), paused);
+
+        result.get_account(&fixture.fundraiser).unwrap().clone()
+    };
+
+    let fundraiser_account = set_paused(fundraiser_account, true);
+
+    let result = contribute(&fixture, fundraiser_account.clone());
+    assert_eq!(result.program_result, ProgramResult::Failure(FundraiserError::Paused.into()));
+
+    let result = fixture.checker(fundraiser_account.clone(), AMOUNT_TO_RAISE, 0, vec![]);
+    assert_eq!(result.program_result, ProgramResult::Failure(FundraiserError::Paused.into()));
+
+    // once resumed, both go through again
+    let fundraiser_account = set_paused(fundraiser_account, false);
+
+    let result = contribute(&fixture, fundraiser_account.clone());
+    assert!(!result.program_result.is_err(), "Program execution failed: {:?}", result.program_result);
+
+    let result = fixture.checker(fundraiser_account, AMOUNT_TO_RAISE, 0, vec![]);
+    assert!(!result.program_result.is_err(), "Program execution failed: {:?}", result.program_result);
+}
+
+#[test]
+fn transfer_fee_mint() {
+    use spl_token_2022::extension::{transfer_fee::TransferFeeConfig, BaseStateWithExtensions, StateWithExtensions};
+
+    const TRANSFER_FEE_BPS: u16 = 100;
+    const CONTRIBUTION: u64 = 1_000_000;
+    let withheld = CONTRIBUTION * TRANSFER_FEE_BPS as u64 / 10_000;
+
+    let mut fixture = Fixture::with_transfer_fee(TRANSFER_FEE_BPS);
+    fixture.mollusk.sysvars.clock.unix_timestamp = TODAY_TIMESTAMP + 60;
+
+    let args = InitializeArgs::new(AMOUNT_TO_RAISE, TODAY_TIMESTAMP, DURATION_DAYS);
+
+    // the mint withholds its fee on the way in, only what reached the vault is credited
+    let contributor = Pubkey::new_unique();
+    let (contributor_account, _) = Pubkey::find_program_address(
+        &[b"contributor", fixture.fundraiser.as_ref(), contributor.as_ref()],
+        &fixture.program_id
+    );
+    let result = fixture.contribute(
+        FundraiserInstructions::Contribute { amount: CONTRIBUTION, intent: None, wrap_sol: false },
+        &contributor,
+        AccountSharedData::new(0, 0, &fixture.system_program),
+        fixture.token_state(&contributor, CONTRIBUTION),
+        fixture.fundraiser_account(&args, 0, |_| {}),
+        None
+    );
+    assert!(!result.program_result.is_err(), "Program execution failed: {:?}", result.program_result);
+
+    assert_eq!(token_amount(&result, &fixture.vault), CONTRIBUTION - withheld);
+    assert_eq!(fundraiser_state(&result, &fixture.fundraiser).current_amount(), CONTRIBUTION - withheld);
+
+    let data = result.get_account(&contributor_account).unwrap().data();
+    assert_eq!(Contributor::load(data).unwrap().amount(), CONTRIBUTION - withheld);
+
+    // fees withheld on the vault are harvested to the mint, otherwise it could not be closed
+    fixture.mollusk.sysvars.clock.unix_timestamp = DEADLINE;
+
+    let fundraiser_account = fixture.fundraiser_account(&args, 0, |fundraiser| {
+        fundraiser.set_current_amount(AMOUNT_TO_RAISE);
+    });
+    let vault = fixture.token_account_withholding(
+        fixture.token_state(&fixture.fundraiser, AMOUNT_TO_RAISE),
+        withheld
+    );
+
+    let maker_ata = (Pubkey::new_unique(), fixture.token_account(fixture.token_state(&fixture.maker, 0)));
+
+    let result = fixture.checker_with(fundraiser_account, vault, maker_ata, 0, false, vec![]);
+    assert!(!result.program_result.is_err(), "Program execution failed: {:?}", result.program_result);
+
+    for account in [fixture.vault, fixture.fundraiser] {
+        assert_eq!(result.get_account(&account).unwrap().lamports(), 0);
+    }
+
+    let data = result.get_account(&fixture.mint_to_raise).unwrap().data();
+    let mint = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(data).unwrap();
+    assert_eq!(u64::from(mint.get_extension::<TransferFeeConfig>().unwrap().withheld_amount), withheld);
+}
+
+#[test]
+fn transfer_hook_accounts() {
+    use crate::token::offchain::resolve_hook_accounts;
+    use spl_transfer_hook_interface::{
+        error::TransferHookError,
+        get_extra_account_metas_address,
+        instruction::TransferHookInstruction
+    };
+    use std::{future::Future, pin::pin, task::{Context, Poll, Waker}};
+
+    let hook_program = Pubkey::new_unique();
+    let mut fixture = Fixture::with_transfer_hook(hook_program);
+    fixture.mollusk.sysvars.clock.unix_timestamp = DEADLINE;
+
+    // the hook's validation account lists a single extra account, as a TLV entry
+    // holding one fixed address that is neither signer nor writable
+    let extra_account = Pubkey::new_unique();
+    let validation = get_extra_account_metas_address(&fixture.mint_to_raise, &hook_program);
+
+    let mut validation_data = TransferHookInstruction::Execute { amount: 0 }.pack()[..8].to_vec();
+    validation_data.extend_from_slice(&(4u32 + 35).to_le_bytes());
+    validation_data.extend_from_slice(&1u32.to_le_bytes());
+    validation_data.push(0);
+    validation_data.extend_from_slice(extra_account.as_ref());
+    validation_data.extend_from_slice(&[0, 0]);
+
+    let mint_data = fixture.mint_account().data().to_vec();
+    let maker_ata = Pubkey::new_unique();
+
+    // clients append the extra accounts, then the hook program and its validation account
+    let resolve = resolve_hook_accounts(
+        |address| {
+            let data = if address == fixture.mint_to_raise {
+                Some(mint_data.clone())
+            } else if address == validation {
+                Some(validation_data.clone())
+            } else {
+                None
+            };
+            async move { Ok(data) }
+        },
+        &fixture.mint_to_raise,
+        &fixture.vault,
+        &maker_ata,
+        &fixture.fundraiser,
+        AMOUNT_TO_RAISE
+    );
+    let Poll::Ready(resolved) = pin!(resolve).poll(&mut Context::from_waker(Waker::noop())) else {
+        panic!("the accounts are fetched synchronously");
+    };
+    assert_eq!(resolved.unwrap(), vec![
+        AccountMeta::new_readonly(extra_account, false),
+        AccountMeta::new_readonly(hook_program, false),
+        AccountMeta::new_readonly(validation, false),
+    ]);
+
+    // without them the payout fails rather than skipping the hook
+    let args = InitializeArgs::new(AMOUNT_TO_RAISE, TODAY_TIMESTAMP, DURATION_DAYS);
+    let fundraiser_account = fixture.fundraiser_account(&args, 0, |fundraiser| {
+        fundraiser.set_current_amount(AMOUNT_TO_RAISE);
+    });
+
+    let result = fixture.checker(fundraiser_account, AMOUNT_TO_RAISE, 0, vec![]);
+    assert_eq!(result.program_result, ProgramResult::Failure(TransferHookError::IncorrectAccount.into()));
+}
+
+#[test]
+fn payout_destination() {
+    use spl_associated_token_account::get_associated_token_address_with_program_id;
+
+    let mut fixture = Fixture::new();
+    fixture.mollusk.sysvars.clock.unix_timestamp = DEADLINE;
+
+    let args = InitializeArgs::new(AMOUNT_TO_RAISE, TODAY_TIMESTAMP, DURATION_DAYS);
+    let fundraiser_account = fixture.fundraiser_account(&args, 0, |fundraiser| {
+        fundraiser.set_current_amount(AMOUNT_TO_RAISE);
+    });
+    let vault = || fixture.token_account(fixture.token_state(&fixture.fundraiser, AMOUNT_TO_RAISE));
+
+    // the payout cannot go to a token account of someone else, or of another mint
+    let other = Pubkey::new_unique();
+    let mut other_mint = fixture.token_state(&fixture.maker, 0);
+    other_mint.mint = Pubkey::new_unique();
+
+    for maker_ata in [fixture.token_state(&other, 0), other_mint] {
+        let result = fixture.checker_with(
+            fundraiser_account.clone(),
+            vault(),
+            (Pubkey::new_unique(), fixture.token_account(maker_ata)),
             0,
-            &system_program
-        );
-
-        let data = [
-            vec![0],
-            AMOUNT_TO_RAISE.to_le_bytes().to_vec(),
-            TODAY_TIMESTAMP.to_le_bytes().to_vec(),
-            DURATION_DAYS.to_le_bytes().to_vec(),
-        ].concat();
-
-        let instruction = Instruction::new_with_bytes(
-            program_id, 
-            &data, 
-            vec![
-                AccountMeta::new(maker, true),  // writable and signer
-                AccountMeta::new_readonly(mint_to_raise, false),  // readonly
-                AccountMeta::new(fundraiser, false),  // writable
-                AccountMeta::new(vault, false),  // writable
-                AccountMeta::new_readonly(system_program, false),  // readonly
-                AccountMeta::new_readonly(token_program, false),  // readonly
+            false,
+            vec![]
+        );
+        assert_eq!(result.program_result, ProgramResult::Failure(ProgramError::InvalidAccountData));
+    }
+
+    // only the maker's associated token account is created for them
+    let result = fixture.checker_with(
+        fundraiser_account.clone(),
+        vault(),
+        (Pubkey::new_unique(), AccountSharedData::new(0, 0, &fixture.system_program)),
+        0,
+        false,
+        vec![]
+    );
+    assert_eq!(result.program_result, ProgramResult::Failure(ProgramError::InvalidSeeds));
+
+    let maker_ata = get_associated_token_address_with_program_id(
+        &fixture.maker,
+        &fixture.mint_to_raise,
+        &fixture.token_program
+    );
+    let result = fixture.checker_with(
+        fundraiser_account,
+        vault(),
+        (maker_ata, AccountSharedData::new(0, 0, &fixture.system_program)),
+        0,
+        false,
+        vec![]
+    );
+    assert!(!result.program_result.is_err(), "Program execution failed: {:?}", result.program_result);
+    assert_eq!(token_amount(&result, &maker_ata), AMOUNT_TO_RAISE);
+}
+
+#[test]
+fn wrapped_sol() {
+    const CONTRIBUTION: u64 = 1_000_000;
+
+    let mut fixture = Fixture::new();
+    fixture.mint_to_raise = spl_token::native_mint::ID;
+    fixture.mollusk.sysvars.clock.unix_timestamp = TODAY_TIMESTAMP + 60;
+
+    let args = InitializeArgs::new(AMOUNT_TO_RAISE, TODAY_TIMESTAMP, DURATION_DAYS);
+
+    // the lamports are sent to the vault and synced into its token balance
+    let contributor = Pubkey::new_unique();
+    let (contributor_account, _) = Pubkey::find_program_address(
+        &[b"contributor", fixture.fundraiser.as_ref(), contributor.as_ref()],
+        &fixture.program_id
+    );
+    let result = fixture.contribute(
+        FundraiserInstructions::Contribute { amount: CONTRIBUTION, intent: None, wrap_sol: true },
+        &contributor,
+        AccountSharedData::new(0, 0, &fixture.system_program),
+        fixture.token_state(&contributor, 0),
+        fixture.fundraiser_account(&args, 0, |_| {}),
+        None
+    );
+    assert!(!result.program_result.is_err(), "Program execution failed: {:?}", result.program_result);
+
+    assert_eq!(token_amount(&result, &fixture.vault), CONTRIBUTION);
+    assert_eq!(fundraiser_state(&result, &fixture.fundraiser).current_amount(), CONTRIBUTION);
+
+    let rent = result.get_account(&contributor_account).unwrap().lamports();
+    assert_eq!(result.get_account(&contributor).unwrap().lamports(), 1_000_000_000 - CONTRIBUTION - rent);
+
+    // lamports someone sent to the vault without syncing them are not credited to the
+    // next contributor
+    fixture.unsynced_lamports = 5_000;
+
+    let result = fixture.contribute(
+        FundraiserInstructions::Contribute { amount: CONTRIBUTION, intent: None, wrap_sol: true },
+        &contributor,
+        AccountSharedData::new(0, 0, &fixture.system_program),
+        fixture.token_state(&contributor, 0),
+        fixture.fundraiser_account(&args, 0, |_| {}),
+        None
+    );
+    assert!(!result.program_result.is_err(), "Program execution failed: {:?}", result.program_result);
+
+    assert_eq!(token_amount(&result, &fixture.vault), CONTRIBUTION + 5_000);
+    assert_eq!(fundraiser_state(&result, &fixture.fundraiser).current_amount(), CONTRIBUTION);
+
+    // the payout is unwrapped through a temporary account, closed to the maker
+    fixture.mollusk.sysvars.clock.unix_timestamp = DEADLINE;
+
+    let fundraiser_account = fixture.fundraiser_account(&args, 0, |fundraiser| {
+        fundraiser.set_current_amount(AMOUNT_TO_RAISE);
+    });
+    let vault = fixture.token_account(fixture.token_state(&fixture.fundraiser, AMOUNT_TO_RAISE));
+    let maker_ata = (Pubkey::new_unique(), fixture.token_account(fixture.token_state(&fixture.maker, 0)));
+    let (unwrap_account, _) = Pubkey::find_program_address(
+        &[b"unwrap", fixture.fundraiser.as_ref(), fixture.maker.as_ref()],
+        &fixture.program_id
+    );
+
+    let closed = vault.lamports() + fundraiser_account.lamports();
+
+    let result = fixture.checker_with(fundraiser_account, vault, maker_ata, 0, true, vec![
+        fixture.unused_account(),
+        (AccountMeta::new(unwrap_account, false), AccountSharedData::new(0, 0, &fixture.system_program)),
+    ]);
+    assert!(!result.program_result.is_err(), "Program execution failed: {:?}", result.program_result);
+
+    // the maker gets the payout and the rent of every closed account as lamports
+    assert_eq!(result.get_account(&fixture.maker).unwrap().lamports(), 1_000_000_000 + closed);
+
+    for account in [unwrap_account, fixture.vault, fixture.fundraiser] {
+        assert_eq!(result.get_account(&account).unwrap().lamports(), 0);
+    }
+}
+
+#[test]
+fn ata_vault() {
+    const CONTRIBUTION: u64 = 1_000_000;
+
+    let mut fixture = Fixture::with_ata_vault();
+    let args = InitializeArgs {
+        ata_vault: true,
+        ..InitializeArgs::new(AMOUNT_TO_RAISE, TODAY_TIMESTAMP, DURATION_DAYS)
+    };
+
+    // the vault is created as the fundraiser's associated token account
+    let result = fixture.initialize(args.clone(), fixture.config_account(0, false), vec![]);
+    assert!(!result.program_result.is_err(), "Program execution failed: {:?}", result.program_result);
+    assert!(fundraiser_state(&result, &fixture.fundraiser).ata_vault());
+
+    let vault = crate::token::unpack_account(result.get_account(&fixture.vault).unwrap().data()).unwrap();
+    assert_eq!(vault.owner, fixture.fundraiser);
+    assert_eq!(vault.mint, fixture.mint_to_raise);
+
+    fixture.mollusk.sysvars.clock.unix_timestamp = TODAY_TIMESTAMP + 60;
+
+    let contributor = Pubkey::new_unique();
+    let contribute = |fixture: &Fixture| fixture.contribute(
+        FundraiserInstructions::Contribute { amount: CONTRIBUTION, intent: None, wrap_sol: false },
+        &contributor,
+        AccountSharedData::new(0, 0, &fixture.system_program),
+        fixture.token_state(&contributor, CONTRIBUTION),
+        fixture.fundraiser_account(&args, 0, |_| {}),
+        None
+    );
+
+    // the PDA vault is not where this fundraiser keeps its tokens
+    let ata = fixture.vault;
+    fixture.vault = Pubkey::find_program_address(&[b"vault", fixture.fundraiser.as_ref()], &fixture.program_id).0;
+
+    let result = contribute(&fixture);
+    assert_eq!(result.program_result, ProgramResult::Failure(ProgramError::InvalidSeeds));
+
+    fixture.vault = ata;
+
+    let result = contribute(&fixture);
+    assert!(!result.program_result.is_err(), "Program execution failed: {:?}", result.program_result);
+    assert_eq!(token_amount(&result, &fixture.vault), CONTRIBUTION);
+
+    // the fundraiser fails and the contribution is refunded out of the same account
+    fixture.mollusk.sysvars.clock.unix_timestamp = DEADLINE;
+
+    let (contributor_account, _) = fixture.contributor_account(&contributor, 0, PledgeStatus::Pending);
+    let result = fixture.refund(
+        &contributor,
+        (contributor_account, result.get_account(&contributor_account).unwrap().clone()),
+        result.get_account(&fixture.fundraiser).unwrap().clone(),
+        CONTRIBUTION,
+        vec![]
+    );
+    assert!(!result.program_result.is_err(), "Program execution failed: {:?}", result.program_result);
+    assert_eq!(token_amount(&result, &fixture.vault), 0);
+}
+
+#[test]
+fn stream_vesting() {
+    use crate::state::stream::Stream;
+
+    const STREAM_DURATION: i64 = 1000;
+
+    let mut fixture = Fixture::new();
+    fixture.mollusk.sysvars.clock.unix_timestamp = TODAY_TIMESTAMP + 60;
+
+    let args = InitializeArgs {
+        stream_duration: STREAM_DURATION,
+        ..InitializeArgs::new(AMOUNT_TO_RAISE, TODAY_TIMESTAMP, DURATION_DAYS)
+    };
+    let fundraiser_account = fixture.fundraiser_account(&args, 0, |fundraiser| {
+        fundraiser.set_current_amount(AMOUNT_TO_RAISE);
+    });
+
+    let (stream, _) = Pubkey::find_program_address(&[b"stream", fixture.fundraiser.as_ref()], &fixture.program_id);
+
+    // the goal is reached, the raised amount starts vesting instead of being paid out
+    let result = fixture.checker(fundraiser_account, AMOUNT_TO_RAISE, 0, vec![
+        (AccountMeta::new(stream, false), AccountSharedData::new(0, 0, &fixture.system_program)),
+    ]);
+    assert!(!result.program_result.is_err(), "Program execution failed: {:?}", result.program_result);
+
+    let stream_data: Stream = borsh::from_slice(result.get_account(&stream).unwrap().data()).unwrap();
+    assert_eq!(stream_data.total, AMOUNT_TO_RAISE);
+    assert_eq!(stream_data.start, TODAY_TIMESTAMP + 60);
+    assert_eq!(token_amount(&result, &fixture.vault), AMOUNT_TO_RAISE);
+
+    let maker_ata = Pubkey::new_unique();
+    let mut accounts = vec![
+        (fixture.maker, AccountSharedData::new(1_000_000_000, 0, &fixture.system_program)),
+        (maker_ata, fixture.token_account(fixture.token_state(&fixture.maker, 0))),
+        (fixture.fundraiser, result.get_account(&fixture.fundraiser).unwrap().clone()),
+        (fixture.vault, result.get_account(&fixture.vault).unwrap().clone()),
+        (stream, result.get_account(&stream).unwrap().clone()),
+        (fixture.token_program, fixture.token_program_account.clone()),
+        (fixture.mint_to_raise, fixture.mint_account()),
+        (fixture.config, fixture.config_account(0, false)),
+    ];
+    let claim_stream = fixture.instruction(FundraiserInstructions::ClaimStream, vec![
+        AccountMeta::new(fixture.maker, true),
+        AccountMeta::new(maker_ata, false),
+        AccountMeta::new(fixture.fundraiser, false),
+        AccountMeta::new(fixture.vault, false),
+        AccountMeta::new(stream, false),
+        AccountMeta::new_readonly(fixture.token_program, false),
+        AccountMeta::new(fixture.mint_to_raise, false),
+        AccountMeta::new_readonly(fixture.config, false),
+    ]);
+
+    // a quarter of the way through, a quarter has vested
+    fixture.mollusk.sysvars.clock.unix_timestamp = stream_data.start + STREAM_DURATION / 4;
+
+    // but nothing can be claimed while the program is paused
+    let mut paused_accounts = accounts.clone();
+    paused_accounts[7].1 = fixture.config_account(0, true);
+    let result = fixture.mollusk.process_instruction(&claim_stream, &paused_accounts);
+    assert_eq!(result.program_result, ProgramResult::Failure(FundraiserError::Paused.into()));
+
+    let result = fixture.mollusk.process_instruction(&claim_stream, &accounts);
+    assert!(!result.program_result.is_err(), "Program execution failed: {:?}", result.program_result);
+    assert_eq!(token_amount(&result, &maker_ata), AMOUNT_TO_RAISE / 4);
+
+    let stream_data: Stream = borsh::from_slice(result.get_account(&stream).unwrap().data()).unwrap();
+    assert_eq!(stream_data.claimed, AMOUNT_TO_RAISE / 4);
+
+    for (key, account) in accounts.iter_mut() {
+        *account = result.get_account(key).unwrap().clone();
+    }
+
+    // claiming again at the same time has nothing to send
+    let result = fixture.mollusk.process_instruction(&claim_stream, &accounts);
+    assert!(!result.program_result.is_err(), "Program execution failed: {:?}", result.program_result);
+    assert_eq!(token_amount(&result, &maker_ata), AMOUNT_TO_RAISE / 4);
+
+    // past the end everything has vested, the final claim closes the accounts
+    fixture.mollusk.sysvars.clock.unix_timestamp = stream_data.start + STREAM_DURATION + 60;
+
+    let result = fixture.mollusk.process_instruction(&claim_stream, &accounts);
+    assert!(!result.program_result.is_err(), "Program execution failed: {:?}", result.program_result);
+    assert_eq!(token_amount(&result, &maker_ata), AMOUNT_TO_RAISE);
+
+    for account in [fixture.vault, fixture.fundraiser, stream] {
+        assert_eq!(result.get_account(&account).unwrap().lamports(), 0);
+    }
+
+    let rent = accounts[0].1.lamports()
+        + accounts[2].1.lamports()
+        + accounts[3].1.lamports()
+        + accounts[4].1.lamports();
+    assert_eq!(result.get_account(&fixture.maker).unwrap().lamports(), rent);
+}
+
+#[test]
+fn release_approved_milestone() {
+    let fixture = Fixture::new();
+
+    let args = InitializeArgs {
+        milestone_shares: vec![50, 50],
+        arbiter: Pubkey::new_unique(),
+        ..InitializeArgs::new(AMOUNT_TO_RAISE, TODAY_TIMESTAMP, DURATION_DAYS)
+    };
+    let fundraiser_account = fixture.fundraiser_account(&args, 0, |fundraiser| {
+        fundraiser.set_current_amount(AMOUNT_TO_RAISE);
+    });
+
+    // the raised amount is locked into milestones and the first one is approved
+    let (milestones, mut milestones_account) = fixture.milestones_account(&args.milestone_shares);
+    let mut milestones_data: Milestones = borsh::from_slice(milestones_account.data()).unwrap();
+    milestones_data.total_raised = AMOUNT_TO_RAISE;
+    milestones_data.milestones[0].approved = true;
+    milestones_account.set_data_from_slice(&borsh::to_vec(&milestones_data).unwrap());
+
+    let maker_ata = Pubkey::new_unique();
+    let release = |paused: bool| fixture.mollusk.process_instruction(
+        &fixture.instruction(FundraiserInstructions::ReleaseMilestone { index: 0 }, vec![
+            AccountMeta::new_readonly(fixture.maker, true),
+            AccountMeta::new(maker_ata, false),
+            AccountMeta::new_readonly(fixture.fundraiser, false),
+            AccountMeta::new(fixture.vault, false),
+            AccountMeta::new(milestones, false),
+            AccountMeta::new_readonly(fixture.token_program, false),
+            AccountMeta::new_readonly(fixture.mint_to_raise, false),
+            AccountMeta::new_readonly(fixture.config, false),
+        ]),
+        &[
+            (fixture.maker, AccountSharedData::new(1_000_000_000, 0, &fixture.system_program)),
+            (maker_ata, fixture.token_account(fixture.token_state(&fixture.maker, 0))),
+            (fixture.fundraiser, fundraiser_account.clone()),
+            (fixture.vault, fixture.token_account(fixture.token_state(&fixture.fundraiser, AMOUNT_TO_RAISE))),
+            (milestones, milestones_account.clone()),
+            (fixture.token_program, fixture.token_program_account.clone()),
+            (fixture.mint_to_raise, fixture.mint_account()),
+            (fixture.config, fixture.config_account(0, paused)),
+        ]
+    );
+
+    // payouts stop while the program is paused
+    let result = release(true);
+    assert_eq!(result.program_result, ProgramResult::Failure(FundraiserError::Paused.into()));
+
+    let result = release(false);
+    assert!(!result.program_result.is_err(), "Program execution failed: {:?}", result.program_result);
+    assert_eq!(token_amount(&result, &maker_ata), AMOUNT_TO_RAISE / 2);
+    assert_eq!(token_amount(&result, &fixture.vault), AMOUNT_TO_RAISE / 2);
+}
+
+#[test]
+fn milestone_amounts() {
+    let mut milestones = Milestones {
+        discriminator: AccountDiscriminator::Milestones as u8,
+        version: Milestones::VERSION,
+        arbiter: Pubkey::default(),
+        total_raised: 950_001,
+        released_amount: 0,
+        abandoned: false,
+        count: 3,
+        milestones: [Milestone::default(); crate::constants::MAX_MILESTONES],
+        bump: 0,
+        total_contributed: 1_000_000,
+    };
+    for (milestone, share) in milestones.milestones.iter_mut().zip([33, 33, 34]) {
+        milestone.share = share;
+    }
+
+    // the shares are taken from the raised amount, rounding down
+    assert_eq!(milestones.tranche_amount(0), 313_500);
+    assert_eq!(milestones.tranche_amount(2), 323_000);
+
+    // nothing released yet, contributions get back what the vault holds after the fee
+    assert_eq!(milestones.refundable_amount(400_000), 380_000);
+    assert_eq!(milestones.refundable_amount(1_000_000), 950_001);
+
+    milestones.milestones[0].released = true;
+    milestones.released_amount = milestones.tranche_amount(0);
+    milestones.milestones[2].released = true;
+    milestones.released_amount += milestones.tranche_amount(2);
+
+    // the last milestone to be released sweeps what rounding left behind
+    assert_eq!(milestones.tranche_amount(1), 313_501);
+
+    // refunds never add up to more than what is left in the vault
+    let contributions = [300_000, 300_000, 400_000];
+    let refunds: Vec<u64> = contributions.iter().map(|amount| milestones.refundable_amount(*amount)).collect();
+    assert_eq!(refunds, [94_050, 94_050, 125_400]);
+    assert!(refunds.iter().sum::<u64>() <= milestones.total_raised - milestones.released_amount);
+}
+
+#[test]
+fn migrate_baseline_accounts() {
+    let fixture = Fixture::new();
+    let payer = Pubkey::new_unique();
+    let contributor = Pubkey::new_unique();
+    let (contributor_account, contributor_bump) = Pubkey::find_program_address(
+        &[b"contributor", fixture.fundraiser.as_ref(), contributor.as_ref()],
+        &fixture.program_id
+    );
+
+    let migrate = |kind: AccountDiscriminator, account: Pubkey, data: Vec<u8>, fundraiser_account: AccountSharedData| {
+        let metas = vec![
+            AccountMeta::new(payer, true),
+            AccountMeta::new(account, false),
+            AccountMeta::new_readonly(fixture.system_program, false),
+            AccountMeta::new(fixture.fundraiser, false),
+            AccountMeta::new_readonly(contributor, false),
+        ];
+        let mut migrated_account = AccountSharedData::new(
+            fixture.mollusk.sysvars.rent.minimum_balance(data.len()),
+            data.len(),
+            &fixture.program_id
+        );
+        migrated_account.set_data_from_slice(&data);
+
+        let mut accounts = vec![
+            (payer, AccountSharedData::new(1_000_000_000, 0, &fixture.system_program)),
+            (account, migrated_account),
+            (fixture.system_program, fixture.system_program_account.clone()),
+            (contributor, AccountSharedData::default()),
+        ];
+        if account != fixture.fundraiser {
+            accounts.push((fixture.fundraiser, fundraiser_account));
+        }
+
+        let result = fixture.mollusk.process_instruction(
+            &fixture.instruction(FundraiserInstructions::Migrate { kind: kind as u8 }, metas),
+            &accounts
+        );
+        assert!(!result.program_result.is_err(), "Program execution failed: {:?}", result.program_result);
+
+        (
+            result.get_account(&account).unwrap().data().to_vec(),
+            result.get_account(&fixture.fundraiser).unwrap().clone()
+        )
+    };
+
+    // the fundraiser as the first release of the program wrote it
+    let mut data = borsh::to_vec(&(
+        fixture.maker,
+        fixture.mint_to_raise,
+        AMOUNT_TO_RAISE,
+        AMOUNT_TO_RAISE / 2,
+        TODAY_TIMESTAMP,
+        DURATION_DAYS,
+        fixture.bump,
+    )).unwrap();
+    data.resize(Fundraiser::V0_LEN, 0);
+
+    let (data, fundraiser_account) = migrate(
+        AccountDiscriminator::Fundraiser,
+        fixture.fundraiser,
+        data,
+        AccountSharedData::default()
+    );
+    let fundraiser = Fundraiser::load(&data).unwrap();
+    assert_eq!(fundraiser.maker, fixture.maker);
+    assert_eq!(fundraiser.mint_to_raise, fixture.mint_to_raise);
+    assert_eq!(fundraiser.amount_to_raise(), AMOUNT_TO_RAISE);
+    assert_eq!(fundraiser.current_amount(), AMOUNT_TO_RAISE / 2);
+    assert_eq!(fundraiser.time_started(), TODAY_TIMESTAMP);
+    assert_eq!(fundraiser.duration, DURATION_DAYS);
+    assert_eq!(fundraiser.bump, fixture.bump);
+    assert_eq!(fundraiser.min_success_percentage, 100);
+    assert_eq!(fundraiser.status().unwrap(), LifecycleStatus::Pending);
+    assert!(!fundraiser.pledge_mode() && !fundraiser.has_milestones() && !fundraiser.ata_vault());
+    // there was no pledge mode, so there is nothing to collect
+    assert_eq!(fundraiser.pending_pledges(), 0);
+    assert_eq!(fundraiser.contributor_count(), 0);
+
+    // and one of its contributors
+    let data = borsh::to_vec(&(AMOUNT_TO_RAISE / 2, contributor_bump)).unwrap();
+    assert_eq!(data.len(), Contributor::V0_LEN);
+
+    let (data, fundraiser_account) = migrate(
+        AccountDiscriminator::Contributor,
+        contributor_account,
+        data,
+        fundraiser_account
+    );
+    let contributor_data = Contributor::load(&data).unwrap();
+    assert_eq!(contributor_data.amount(), AMOUNT_TO_RAISE / 2);
+    assert_eq!(contributor_data.bump, contributor_bump);
+    assert_eq!(contributor_data.pledge_status(), PledgeStatus::Pending);
+    assert_eq!(contributor_data.intent_nonce(), 0);
+    assert_eq!(contributor_data.rent_payer, contributor);
+
+    // it is counted on its fundraiser, so refunding it takes the count back to 0
+    assert_eq!(Fundraiser::load(fundraiser_account.data()).unwrap().contributor_count(), 1);
+
+    // the header is checked before the length, so a layout this program does not know
+    // is reported as such
+    let mut data = data;
+    data[1] = Contributor::VERSION + 1;
+    data.push(0);
+    assert_eq!(
+        Contributor::load(&data).unwrap_err(),
+        FundraiserError::AccountVersionMismatch.into()
+    );
+}
+
+#[test]
+fn close_contributor_returns_rent_to_payer() {
+    let fixture = Fixture::new();
+    let contributor = Pubkey::new_unique();
+    let relayer = Pubkey::new_unique();
+
+    // a relayer paid for the contributor account when submitting an intent
+    let (contributor_account, mut account) = fixture.contributor_account(&contributor, 1_000_000, PledgeStatus::Pending);
+    Contributor::load_mut(account.data_as_mut_slice()).unwrap().rent_payer = relayer;
+    let rent = account.lamports();
+
+    // closed by `checker` once the maker was paid out
+    let close = |rent_payer: &Pubkey| fixture.close_contributor(
+        &contributor,
+        (contributor_account, account.clone()),
+        AccountSharedData::new(0, 0, &fixture.system_program),
+        rent_payer
+    );
+
+    // the contributor did not pay, so the rent is not theirs
+    let result = close(&contributor);
+    assert_eq!(result.program_result, ProgramResult::Failure(FundraiserError::InvalidRentPayer.into()));
+
+    let result = close(&relayer);
+    assert!(!result.program_result.is_err(), "Program execution failed: {:?}", result.program_result);
+    assert_eq!(result.get_account(&relayer).unwrap().lamports(), rent);
+    assert_eq!(result.get_account(&contributor_account).unwrap().lamports(), 0);
+}
+
+#[test]
+fn sponsored_contributor_rent() {
+    const CONTRIBUTION: u64 = 1_000_000;
+    const RENT_POOL: u64 = 100_000_000;
+
+    let mut fixture = Fixture::new();
+    fixture.mollusk.sysvars.clock.unix_timestamp = TODAY_TIMESTAMP + 60;
+    fixture.rent_pool_lamports = RENT_POOL;
+    let rent = fixture.mollusk.sysvars.rent.minimum_balance(Contributor::LEN);
+
+    let args = InitializeArgs::new(AMOUNT_TO_RAISE, TODAY_TIMESTAMP, DURATION_DAYS);
+    let contributor = Pubkey::new_unique();
+    let result = fixture.contribute(
+        FundraiserInstructions::Contribute { amount: CONTRIBUTION, intent: None, wrap_sol: false },
+        &contributor,
+        AccountSharedData::new(0, 0, &fixture.system_program),
+        fixture.token_state(&contributor, CONTRIBUTION),
+        fixture.fundraiser_account(&args, 0, |_| {}),
+        None
+    );
+    assert!(!result.program_result.is_err(), "Program execution failed: {:?}", result.program_result);
+
+    // the pool pays the rent, the contributor only sends tokens
+    assert_eq!(result.get_account(&fixture.rent_pool).unwrap().lamports(), RENT_POOL - rent);
+    assert_eq!(result.get_account(&contributor).unwrap().lamports(), 1_000_000_000);
+
+    let (contributor_account, _) = fixture.contributor_account(&contributor, 0, PledgeStatus::Pending);
+    let account = result.get_account(&contributor_account).unwrap().clone();
+    assert_eq!(account.lamports(), rent);
+    assert!(Contributor::load(account.data()).unwrap().rent_sponsored());
+    assert_eq!(Contributor::load(account.data()).unwrap().rent_payer, fixture.rent_pool);
+
+    // the account is needed for refunds until the fundraiser is settled
+    let fundraiser_account = result.get_account(&fixture.fundraiser).unwrap().clone();
+    let result = fixture.close_contributor(
+        &contributor,
+        (contributor_account, account.clone()),
+        fundraiser_account.clone(),
+        &fixture.rent_pool
+    );
+    assert_eq!(result.program_result, ProgramResult::Failure(FundraiserError::ContributorAccountInUse.into()));
+
+    // the fundraiser fails, the refund hands the rent back to the pool
+    fixture.mollusk.sysvars.clock.unix_timestamp = DEADLINE;
+
+    let result = fixture.refund(
+        &contributor,
+        (contributor_account, account.clone()),
+        fundraiser_account.clone(),
+        CONTRIBUTION,
+        vec![]
+    );
+    assert!(!result.program_result.is_err(), "Program execution failed: {:?}", result.program_result);
+    assert_eq!(result.get_account(&fixture.rent_pool).unwrap().lamports(), rent);
+    assert_eq!(result.get_account(&contributor_account).unwrap().lamports(), 0);
+    assert_eq!(result.get_account(&contributor).unwrap().lamports(), 1_000_000_000);
+
+    // or the fundraiser succeeds and is settled, closing the account does the same
+    let mut settled_account = fundraiser_account;
+    let fundraiser = Fundraiser::load_mut(settled_account.data_as_mut_slice()).unwrap();
+    fundraiser.transition(LifecycleStatus::Succeeded).unwrap();
+    fundraiser.transition(LifecycleStatus::Settled).unwrap();
+
+    let result = fixture.close_contributor(
+        &contributor,
+        (contributor_account, account),
+        settled_account,
+        &fixture.rent_pool
+    );
+    assert!(!result.program_result.is_err(), "Program execution failed: {:?}", result.program_result);
+    assert_eq!(result.get_account(&fixture.rent_pool).unwrap().lamports(), rent);
+    assert_eq!(result.get_account(&contributor_account).unwrap().lamports(), 0);
+}
+
+#[test]
+fn withdraw_rent_pool_by_maker() {
+    const RENT_POOL: u64 = 50_000_000;
+
+    let fixture = Fixture::new();
+
+    let withdraw = |signer: &Pubkey| fixture.mollusk.process_instruction(
+        &fixture.instruction(FundraiserInstructions::WithdrawRentPool, vec![
+            AccountMeta::new(*signer, true),
+            AccountMeta::new_readonly(fixture.fundraiser, false),
+            AccountMeta::new(fixture.rent_pool, false),
+            AccountMeta::new_readonly(fixture.system_program, false),
+        ]),
+        &[
+            (*signer, AccountSharedData::new(1_000_000_000, 0, &fixture.system_program)),
+            // closed by `checker` once the maker was paid out
+            (fixture.fundraiser, AccountSharedData::new(0, 0, &fixture.system_program)),
+            (fixture.rent_pool, AccountSharedData::new(RENT_POOL, 0, &fixture.system_program)),
+            (fixture.system_program, fixture.system_program_account.clone()),
+        ]
+    );
+
+    // the pool is the maker's, nobody else can empty it
+    let result = withdraw(&Pubkey::new_unique());
+    assert_eq!(result.program_result, ProgramResult::Failure(ProgramError::InvalidSeeds));
+
+    let result = withdraw(&fixture.maker);
+    assert!(!result.program_result.is_err(), "Program execution failed: {:?}", result.program_result);
+    assert_eq!(result.get_account(&fixture.maker).unwrap().lamports(), 1_000_000_000 + RENT_POOL);
+    assert_eq!(result.get_account(&fixture.rent_pool).unwrap().lamports(), 0);
+}
+
+#[test]
+fn contribute_with_intent() {
+    use crate::{instructions::ContributionIntentArgs, intent::ContributionIntent};
+
+    const CONTRIBUTION: u64 = 1_000_000;
+
+    let mut fixture = Fixture::new();
+    fixture.mollusk.sysvars.clock.unix_timestamp = TODAY_TIMESTAMP + 60;
+    fixture.mollusk.sysvars.clock.slot = 1_000;
+
+    let contributor = Pubkey::new_unique();
+    let relayer = Pubkey::new_unique();
+    let args = InitializeArgs::new(AMOUNT_TO_RAISE, TODAY_TIMESTAMP, DURATION_DAYS);
+
+    let intent = ContributionIntent {
+        program_id: fixture.program_id,
+        fundraiser: fixture.fundraiser,
+        contributor,
+        amount: CONTRIBUTION,
+        nonce: 1,
+        expires_at: DEADLINE,
+    };
+
+    // the relayer submits an intent signed by `signer` over `message`, `last_nonce`
+    // being the nonce already stored on the contributor account, if there is one
+    let submit = |intent: &ContributionIntent, signer: &Pubkey, message: Vec<u8>, delegated_amount: u64, last_nonce: Option<u64>| {
+        let contributor_account = match last_nonce {
+            Some(nonce) => {
+                let (_, mut account) = fixture.contributor_account(&contributor, CONTRIBUTION, PledgeStatus::Pending);
+                Contributor::load_mut(account.data_as_mut_slice()).unwrap().set_intent_nonce(nonce);
+                account
+            },
+            None => AccountSharedData::new(0, 0, &fixture.system_program),
+        };
+
+        let mut contributor_ata = fixture.token_state(&contributor, CONTRIBUTION);
+        contributor_ata.delegate = COption::Some(fixture.fundraiser);
+        contributor_ata.delegated_amount = delegated_amount;
+
+        let contribute_instruction = FundraiserInstructions::Contribute {
+            amount: intent.amount,
+            intent: Some(ContributionIntentArgs { nonce: intent.nonce, expires_at: intent.expires_at }),
+            wrap_sol: false,
+        };
+
+        fixture.contribute(
+            contribute_instruction.clone(),
+            &contributor,
+            contributor_account,
+            contributor_ata,
+            fixture.fundraiser_account(&args, 0, |_| {}),
+            Some((&relayer, instructions_sysvar(&[
+                ed25519_instruction(signer, &message),
+                fixture.instruction(contribute_instruction, vec![]),
+            ])))
+        )
+    };
+
+    // the relayer pays for the contributor account, the contributor does not sign
+    let result = submit(&intent, &contributor, intent.message(), CONTRIBUTION, None);
+    assert!(!result.program_result.is_err(), "Program execution failed: {:?}", result.program_result);
+    assert_eq!(token_amount(&result, &fixture.vault), CONTRIBUTION);
+    assert_eq!(result.get_account(&contributor).unwrap().lamports(), 1_000_000_000);
+    assert!(result.get_account(&relayer).unwrap().lamports() < 1_000_000_000);
+
+    let (contributor_account, _) = fixture.contributor_account(&contributor, 0, PledgeStatus::Pending);
+    let data = result.get_account(&contributor_account).unwrap().data();
+    assert_eq!(Contributor::load(data).unwrap().amount(), CONTRIBUTION);
+    assert_eq!(Contributor::load(data).unwrap().intent_nonce(), 1);
+    assert_eq!(Contributor::load(data).unwrap().contributed_slot(), 1_000);
+    assert_eq!(Contributor::load(data).unwrap().rent_payer, relayer);
+
+    let invalid_intent = ProgramResult::Failure(FundraiserError::InvalidIntent.into());
+
+    // signed by someone else
+    let result = submit(&intent, &relayer, intent.message(), CONTRIBUTION, None);
+    assert_eq!(result.program_result, invalid_intent);
+
+    // signed over another amount
+    let message = ContributionIntent { amount: CONTRIBUTION * 2, ..intent.clone() }.message();
+    let result = submit(&intent, &contributor, message, CONTRIBUTION, None);
+    assert_eq!(result.program_result, invalid_intent);
+
+    // the approval has to be used up by the contribution
+    let result = submit(&intent, &contributor, intent.message(), CONTRIBUTION * 2, None);
+    assert_eq!(result.program_result, invalid_intent);
+
+    // valid past the fundraiser's deadline
+    let late = ContributionIntent { expires_at: DEADLINE + 1, ..intent.clone() };
+    let result = submit(&late, &contributor, late.message(), CONTRIBUTION, None);
+    assert_eq!(result.program_result, invalid_intent);
+
+    // expired
+    let expired = ContributionIntent { expires_at: TODAY_TIMESTAMP + 30, ..intent.clone() };
+    let result = submit(&expired, &contributor, expired.message(), CONTRIBUTION, None);
+    assert_eq!(result.program_result, ProgramResult::Failure(FundraiserError::IntentExpired.into()));
+
+    // already submitted
+    let result = submit(&intent, &contributor, intent.message(), CONTRIBUTION, Some(1));
+    assert_eq!(result.program_result, ProgramResult::Failure(FundraiserError::IntentReplayed.into()));
+
+    // a later intent goes through
+    let next = ContributionIntent { nonce: 2, ..intent.clone() };
+    let result = submit(&next, &contributor, next.message(), CONTRIBUTION, Some(1));
+    assert!(!result.program_result.is_err(), "Program execution failed: {:?}", result.program_result);
+}
+
+#[test]
+fn governance_refund_everyone() {
+    use crate::state::proposal::{Proposal, ProposalKind};
+
+    const VOTING_PERIOD: i64 = 3600;
+
+    let mut fixture = Fixture::new();
+    fixture.mollusk.sysvars.clock.unix_timestamp = TODAY_TIMESTAMP + 60;
+    fixture.mollusk.sysvars.clock.slot = 1_000;
+
+    let args = InitializeArgs {
+        quorum: 50,
+        voting_period: VOTING_PERIOD,
+        ..InitializeArgs::new(AMOUNT_TO_RAISE, TODAY_TIMESTAMP, DURATION_DAYS)
+    };
+    let fundraiser_account = fixture.fundraiser_account(&args, 0, |fundraiser| {
+        fundraiser.set_current_amount(AMOUNT_TO_RAISE / 2);
+        fundraiser.set_contributor_count(2);
+    });
+
+    let contributor = Pubkey::new_unique();
+    let contributor_account = fixture.contributor_account(&contributor, AMOUNT_TO_RAISE * 3 / 10, PledgeStatus::Pending);
+    let (proposal, _) = Pubkey::find_program_address(
+        &[b"proposal", fixture.fundraiser.as_ref(), &0u64.to_le_bytes()],
+        &fixture.program_id
+    );
+
+    let create_proposal = |proposer: &Pubkey, contributor_account: &(Pubkey, AccountSharedData), fundraiser_account: AccountSharedData| {
+        fixture.mollusk.process_instruction(
+            &fixture.instruction(
+                FundraiserInstructions::CreateProposal { kind: ProposalKind::RefundEveryone, argument: 0 },
+                vec![
+                    AccountMeta::new(*proposer, true),
+                    AccountMeta::new(fixture.fundraiser, false),
+                    AccountMeta::new(proposal, false),
+                    AccountMeta::new_readonly(fixture.system_program, false),
+                    AccountMeta::new_readonly(contributor_account.0, false),
+                ]
+            ),
+            &[
+                (*proposer, AccountSharedData::new(1_000_000_000, 0, &fixture.system_program)),
+                (fixture.fundraiser, fundraiser_account),
+                (proposal, AccountSharedData::new(0, 0, &fixture.system_program)),
+                (fixture.system_program, fixture.system_program_account.clone()),
+                contributor_account.clone(),
             ]
+        )
+    };
+
+    // a pledge that failed to be collected carries no weight
+    let failed = Pubkey::new_unique();
+    let failed_account = fixture.contributor_account(&failed, AMOUNT_TO_RAISE / 5, PledgeStatus::Failed);
+    let result = create_proposal(&failed, &failed_account, fundraiser_account.clone());
+    assert_eq!(result.program_result, ProgramResult::Failure(ProgramError::InvalidAccountData));
+
+    let result = create_proposal(&contributor, &contributor_account, fundraiser_account);
+    assert!(!result.program_result.is_err(), "Program execution failed: {:?}", result.program_result);
+
+    let proposal_data: Proposal = borsh::from_slice(result.get_account(&proposal).unwrap().data()).unwrap();
+    assert_eq!(proposal_data.total_weight, AMOUNT_TO_RAISE / 2);
+    assert_eq!(proposal_data.voting_ends, TODAY_TIMESTAMP + 60 + VOTING_PERIOD);
+    assert_eq!(proposal_data.created_slot, 1_000);
+
+    let vote = |voter: &Pubkey, voter_account: &(Pubkey, AccountSharedData), result: &mollusk_svm::result::InstructionResult| {
+        let (vote_record, _) = Pubkey::find_program_address(
+            &[b"vote", proposal.as_ref(), voter.as_ref()],
+            &fixture.program_id
         );
 
-        let result: mollusk_svm::result::InstructionResult = mollusk.process_instruction(
-            &instruction, 
-            &vec![
-                (
-                    maker, 
-                    AccountSharedData::new(1_000_000_000_000, 0, &Pubkey::default())
-                ),
-                (
-                    mint_to_raise,
-                    mint_to_raise_account
-                ),
-                (
-                    fundraiser,
-                    fundraiser_account
-                ),
-                (
-                    vault,
-                    vault_account
-                ),
-                (system_program, system_program_account),
-                (token_program, token_program_account)
-            ],
-        );
-        println!("vault: {}", vault.to_string());
-        assert!(!result.program_result.is_err(), "Program execution failed: {:?}", result.program_result);    }
-}
\ No newline at end of file
+        fixture.mollusk.process_instruction(
+            &fixture.instruction(FundraiserInstructions::Vote { approve: true }, vec![
+                AccountMeta::new(*voter, true),
+                AccountMeta::new_readonly(voter_account.0, false),
+                AccountMeta::new(fixture.fundraiser, false),
+                AccountMeta::new(proposal, false),
+                AccountMeta::new(vote_record, false),
+                AccountMeta::new_readonly(fixture.system_program, false),
+            ]),
+            &[
+                (*voter, AccountSharedData::new(1_000_000_000, 0, &fixture.system_program)),
+                voter_account.clone(),
+                (fixture.fundraiser, result.get_account(&fixture.fundraiser).unwrap().clone()),
+                (proposal, result.get_account(&proposal).unwrap().clone()),
+                (vote_record, AccountSharedData::new(0, 0, &fixture.system_program)),
+                (fixture.system_program, fixture.system_program_account.clone()),
+            ]
+        )
+    };
+
+    // a contribution made once the proposal is open was not part of its total weight
+    let late = Pubkey::new_unique();
+    let mut late_account = fixture.contributor_account(&late, AMOUNT_TO_RAISE, PledgeStatus::Pending);
+    Contributor::load_mut(late_account.1.data_as_mut_slice()).unwrap().set_contributed_slot(1_000);
+    let late_result = vote(&late, &late_account, &result);
+    assert_eq!(late_result.program_result, ProgramResult::Failure(FundraiserError::ContributedAfterProposal.into()));
+
+    let result = vote(&contributor, &contributor_account, &result);
+    assert!(!result.program_result.is_err(), "Program execution failed: {:?}", result.program_result);
+
+    let proposal_data: Proposal = borsh::from_slice(result.get_account(&proposal).unwrap().data()).unwrap();
+    assert_eq!(proposal_data.votes_for, AMOUNT_TO_RAISE * 3 / 10);
+
+    // contributions made while voting would have kept the 30% that voted below a
+    // quorum of half the live amount
+    let mut fundraiser_account = result.get_account(&fixture.fundraiser).unwrap().clone();
+    Fundraiser::load_mut(fundraiser_account.data_as_mut_slice()).unwrap().set_current_amount(AMOUNT_TO_RAISE);
+    let proposal_account = result.get_account(&proposal).unwrap().clone();
+
+    let execute = |fixture: &Fixture| fixture.mollusk.process_instruction(
+        &fixture.instruction(FundraiserInstructions::ExecuteProposal, vec![
+            AccountMeta::new(fixture.fundraiser, false),
+            AccountMeta::new(proposal, false),
+        ]),
+        &[
+            (fixture.fundraiser, fundraiser_account.clone()),
+            (proposal, proposal_account.clone()),
+        ]
+    );
+
+    let result = execute(&fixture);
+    assert_eq!(result.program_result, ProgramResult::Failure(FundraiserError::VotingNotEnded.into()));
+
+    fixture.mollusk.sysvars.clock.unix_timestamp = proposal_data.voting_ends;
+
+    let result = execute(&fixture);
+    assert!(!result.program_result.is_err(), "Program execution failed: {:?}", result.program_result);
+    assert_eq!(fundraiser_state(&result, &fixture.fundraiser).status().unwrap(), LifecycleStatus::Cancelled);
+
+    let proposal_data: Proposal = borsh::from_slice(result.get_account(&proposal).unwrap().data()).unwrap();
+    assert!(proposal_data.executed);
+}
+
+#[test]
+fn decode_event_logs() {
+    use base64::{engine::general_purpose::STANDARD, Engine};
+    use crate::events::{decoder::decode_logs, FundraiserEvent};
+
+    let program_id = pubkey!("22222222222222222222222222222222222222222222");
+
+    let event = FundraiserEvent::Contributed {
+        fundraiser: Pubkey::new_unique(),
+        contributor: Pubkey::new_unique(),
+        amount: 1_000_000,
+        current_amount: 3_000_000,
+    };
+    let data = STANDARD.encode(borsh::to_vec(&event).unwrap());
+
+    let logs = vec![
+        format!("Program {} invoke [1]", program_id),
+        format!("Program {} invoke [2]", spl_token::ID),
+        format!("Program data: {}", data),
+        format!("Program {} success", spl_token::ID),
+        format!("Program data: {}", data),
+        format!("Program {} consumed 4000 of 200000 compute units", program_id),
+        format!("Program {} success", program_id),
+    ];
+
+    // the line logged while the token program was executing is not ours
+    assert_eq!(decode_logs(&logs), vec![event]);
+}
+
+#[test]
+fn initialize_checks_mint_extensions() {
+    use crate::token::{EXTENSION_PERMANENT_DELEGATE, EXTENSION_TRANSFER_FEE};
+
+    let fixture = Fixture::with_transfer_fee(100);
+
+    let config_rejecting = |rejected_extensions: u16| {
+        let mut account = fixture.config_account(0, false);
+        let mut config: Config = borsh::from_slice(account.data()).unwrap();
+        config.rejected_extensions = rejected_extensions;
+        account.set_data_from_slice(&borsh::to_vec(&config).unwrap());
+
+        account
+    };
+    let args = InitializeArgs::new(AMOUNT_TO_RAISE, TODAY_TIMESTAMP, DURATION_DAYS);
+
+    let result = fixture.initialize(args.clone(), config_rejecting(EXTENSION_TRANSFER_FEE), vec![]);
+    assert_eq!(result.program_result, ProgramResult::Failure(FundraiserError::MintExtensionRejected.into()));
+
+    // extensions the config does not reject are recorded on the fundraiser
+    let result = fixture.initialize(args, config_rejecting(EXTENSION_PERMANENT_DELEGATE), vec![]);
+    assert!(!result.program_result.is_err(), "Program execution failed: {:?}", result.program_result);
+    assert_eq!(fundraiser_state(&result, &fixture.fundraiser).mint_extensions(), EXTENSION_TRANSFER_FEE);
+}
+
+#[test]
+fn inspect_mint_extensions() {
+    use crate::token::{inspect_mint, EXTENSION_FREEZE_AUTHORITY};
+
+    let mut mint = spl_token::state::Mint {
+        mint_authority: COption::None,
+        supply: 100_000_000,
+        decimals: 6,
+        is_initialized: true,
+        freeze_authority: COption::None,
+    };
+    let mut data = vec![0; spl_token::state::Mint::LEN];
+
+    spl_token::state::Mint::pack(mint, &mut data).unwrap();
+    assert_eq!(inspect_mint(&data), Ok(0));
+
+    // a freeze authority can lock the vault, token program mints included
+    mint.freeze_authority = COption::Some(Pubkey::new_unique());
+    spl_token::state::Mint::pack(mint, &mut data).unwrap();
+    assert_eq!(inspect_mint(&data), Ok(EXTENSION_FREEZE_AUTHORITY));
+
+    assert!(inspect_mint(&data[..40]).is_err());
+}
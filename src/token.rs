@@ -0,0 +1,433 @@
+//! Token program helpers. Fundraisers can raise either a token program or a Token-2022
+//! mint, the vault and every token account involved then belong to that same program.
+
+use solana_program::{
+    account_info::AccountInfo, 
+    clock::Clock, 
+    entrypoint::ProgramResult, 
+    program::{invoke, invoke_signed}, 
+    program_error::ProgramError, 
+    program_pack::Pack, 
+    pubkey::Pubkey, 
+    rent::Rent, 
+    system_instruction::{allocate, assign, create_account, transfer}, 
+    sysvar::Sysvar
+};
+use spl_token_2022::{
+    extension::{
+        permanent_delegate::PermanentDelegate,
+        transfer_fee::{instruction::transfer_checked_with_fee, TransferFeeConfig},
+        transfer_hook::{self, TransferHook},
+        BaseStateWithExtensions,
+        ExtensionType,
+        StateWithExtensions
+    },
+    instruction::{close_account, initialize_account3},
+    state::{Account, Mint},
+};
+use spl_associated_token_account::{
+    get_associated_token_address_with_program_id,
+    instruction::create_associated_token_account_idempotent
+};
+use spl_transfer_hook_interface::onchain::add_extra_accounts_for_execute_cpi;
+
+use crate::error::FundraiserError;
+
+/// The mint has a permanent delegate, which can move tokens out of the vault
+pub const EXTENSION_PERMANENT_DELEGATE: u16 = 1 << 0;
+/// The mint has a freeze authority, which can freeze the vault
+pub const EXTENSION_FREEZE_AUTHORITY: u16 = 1 << 1;
+/// Transfers run a hook program, which can refuse payouts and refunds
+pub const EXTENSION_TRANSFER_HOOK: u16 = 1 << 2;
+/// Tokens cannot be transferred at all
+pub const EXTENSION_NON_TRANSFERABLE: u16 = 1 << 3;
+/// Balances can be moved into confidential transfers the vault accounting does not see
+pub const EXTENSION_CONFIDENTIAL_TRANSFERS: u16 = 1 << 4;
+/// Transfers withhold a fee, so the recipient receives less than was sent
+pub const EXTENSION_TRANSFER_FEE: u16 = 1 << 5;
+
+/// Whether the mint is wrapped SOL of either token program.
+#[inline]
+pub fn is_native_mint(mint: &Pubkey) -> bool {
+    *mint == spl_token::native_mint::ID || *mint == spl_token_2022::native_mint::ID
+}
+
+#[inline]
+pub fn check_token_program(token_program: &Pubkey) -> ProgramResult {
+    if !spl_token::check_id(token_program) && !spl_token_2022::check_id(token_program) {
+        return Err(ProgramError::IncorrectProgramId)
+    }
+
+    Ok(())
+}
+
+/// Classifies the mint's features that can drain or lock a vault into `EXTENSION_*`
+/// flags. Extensions that only change how the mint is displayed are left out.
+pub fn inspect_mint(data: &[u8]) -> Result<u16, ProgramError> {
+    let mint = StateWithExtensions::<Mint>::unpack(data)
+        .map_err(|_| FundraiserError::InvalidMint)?;
+
+    let mut extensions = 0;
+
+    if mint.base.freeze_authority.is_some() {
+        extensions |= EXTENSION_FREEZE_AUTHORITY;
+    }
+
+    let extension_types = mint.get_extension_types()
+        .map_err(|_| FundraiserError::InvalidMint)?;
+
+    for extension_type in extension_types {
+        extensions |= match extension_type {
+            ExtensionType::PermanentDelegate => {
+                let delegate = mint.get_extension::<PermanentDelegate>()?.delegate;
+
+                if Option::<Pubkey>::from(delegate).is_some() { EXTENSION_PERMANENT_DELEGATE } else { 0 }
+            },
+            ExtensionType::TransferHook => {
+                let program_id = mint.get_extension::<TransferHook>()?.program_id;
+
+                if Option::<Pubkey>::from(program_id).is_some() { EXTENSION_TRANSFER_HOOK } else { 0 }
+            },
+            ExtensionType::NonTransferable => EXTENSION_NON_TRANSFERABLE,
+            ExtensionType::ConfidentialTransferMint
+                | ExtensionType::ConfidentialTransferFeeConfig => EXTENSION_CONFIDENTIAL_TRANSFERS,
+            // the fee can be raised later, so a zero fee is flagged too
+            ExtensionType::TransferFeeConfig => EXTENSION_TRANSFER_FEE,
+            _ => 0,
+        };
+    }
+
+    Ok(extensions)
+}
+
+/// Size of a token account for the mint, Token-2022 mints can require account extensions.
+pub fn account_len(token_program: &Pubkey, mint_data: &[u8]) -> Result<usize, ProgramError> {
+    if spl_token::check_id(token_program) {
+        return Ok(spl_token::state::Account::LEN)
+    }
+
+    let mint = StateWithExtensions::<Mint>::unpack(mint_data)
+        .map_err(|_| FundraiserError::InvalidMint)?;
+
+    let account_extensions = ExtensionType::get_required_init_account_extensions(
+        &mint.get_extension_types()?
+    );
+
+    ExtensionType::try_calculate_account_len::<Account>(&account_extensions)
+}
+
+/// Base state of a token account of either token program.
+#[inline]
+pub fn unpack_account(data: &[u8]) -> Result<Account, ProgramError> {
+    Ok(StateWithExtensions::<Account>::unpack(data)?.base)
+}
+
+/// Token balance of a token account of either token program. Fees withheld on the
+/// account by a transfer fee mint are not part of it.
+#[inline]
+pub fn balance(account: &AccountInfo) -> Result<u64, ProgramError> {
+    Ok(unpack_account(&account.try_borrow_data()?)?.amount)
+}
+
+/// Whether the mint withholds a fee on transfers, in which case the vault has to be
+/// harvested before it can be closed.
+#[inline]
+pub fn has_transfer_fee(mint: &AccountInfo) -> Result<bool, ProgramError> {
+    let mint_data = mint.try_borrow_data()?;
+
+    Ok(StateWithExtensions::<Mint>::unpack(&mint_data)?.get_extension::<TransferFeeConfig>().is_ok())
+}
+
+/// Checks a token account receiving funds belongs to `owner` and holds `mint`.
+pub fn check_destination(
+    account: &AccountInfo,
+    owner: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey
+) -> ProgramResult {
+    if account.owner != token_program {
+        return Err(ProgramError::InvalidAccountOwner)
+    }
+
+    let token_account = unpack_account(&account.try_borrow_data()?)?;
+
+    if token_account.owner != *owner || token_account.mint != *mint {
+        return Err(ProgramError::InvalidAccountData)
+    }
+
+    Ok(())
+}
+
+/// Creates the associated token account of `owner` for `mint`, `payer` funding its rent.
+/// Does nothing if it already exists.
+pub fn create_ata_idempotent<'a>(
+    payer: &AccountInfo<'a>,
+    ata: &AccountInfo<'a>,
+    owner: &AccountInfo<'a>,
+    mint: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    token_program: &AccountInfo<'a>,
+    associated_token_program: &AccountInfo<'a>
+) -> ProgramResult {
+    if !spl_associated_token_account::check_id(associated_token_program.key) {
+        return Err(ProgramError::IncorrectProgramId)
+    }
+
+    if get_associated_token_address_with_program_id(owner.key, mint.key, token_program.key) != *ata.key {
+        return Err(ProgramError::InvalidSeeds)
+    }
+
+    let create_ata_ix = create_associated_token_account_idempotent(
+        payer.key, 
+        owner.key, 
+        mint.key, 
+        token_program.key
+    );
+
+    invoke(
+        &create_ata_ix, 
+        &[
+            payer.clone(),
+            ata.clone(),
+            owner.clone(),
+            mint.clone(),
+            system_program.clone(),
+            token_program.clone(),
+            associated_token_program.clone()
+        ]
+    )
+}
+
+/// Accounts of a `transfer_checked`.
+pub struct TransferAccounts<'a, 'b> {
+    pub token_program: &'b AccountInfo<'a>,
+    pub source: &'b AccountInfo<'a>,
+    pub mint: &'b AccountInfo<'a>,
+    pub destination: &'b AccountInfo<'a>,
+    pub authority: &'b AccountInfo<'a>,
+    /// The hook program, its validation account and every account it lists, empty when
+    /// the mint has no transfer hook
+    pub hook_accounts: &'b [AccountInfo<'a>],
+}
+
+/// Runs a `transfer_checked` through either token program.
+///
+/// With a transfer fee mint the fee due in the current epoch is passed along, so the
+/// recipient gets `amount` minus that fee and the transfer fails if the fee changed under
+/// it. With a transfer hook mint the hook's extra accounts are picked from `hook_accounts`.
+pub fn transfer_checked(
+    accounts: &TransferAccounts,
+    amount: u64,
+    signers_seeds: &[&[&[u8]]]
+) -> ProgramResult {
+    let TransferAccounts { token_program, source, mint, destination, authority, hook_accounts } = *accounts;
+
+    let (mut transfer_ix, hook_program_id) = {
+        let mint_data = mint.try_borrow_data()?;
+        let mint_state = StateWithExtensions::<Mint>::unpack(&mint_data)?;
+        let decimals = mint_state.base.decimals;
+
+        let transfer_ix = match mint_state.get_extension::<TransferFeeConfig>() {
+            Ok(fee_config) => {
+                let fee = fee_config.calculate_epoch_fee(Clock::get()?.epoch, amount)
+                    .ok_or(ProgramError::ArithmeticOverflow)?;
+
+                transfer_checked_with_fee(
+                    token_program.key, source.key, mint.key, destination.key, authority.key, &[], amount, decimals, fee
+                )?
+            },
+            Err(_) => spl_token_2022::instruction::transfer_checked(
+                token_program.key, source.key, mint.key, destination.key, authority.key, &[], amount, decimals
+            )?,
+        };
+
+        (transfer_ix, transfer_hook::get_program_id(&mint_state))
+    };
+
+    let mut account_infos = vec![
+        source.clone(),
+        mint.clone(),
+        destination.clone(),
+        authority.clone(),
+        token_program.clone(),
+    ];
+
+    if let Some(hook_program_id) = hook_program_id {
+        add_extra_accounts_for_execute_cpi(
+            &mut transfer_ix,
+            &mut account_infos,
+            &hook_program_id,
+            source.clone(),
+            mint.clone(),
+            destination.clone(),
+            authority.clone(),
+            amount,
+            hook_accounts
+        )?;
+    }
+
+    invoke_signed(&transfer_ix, &account_infos, signers_seeds)
+}
+
+/// Accounts of an `unwrap_to`.
+pub struct UnwrapAccounts<'a, 'b> {
+    pub token_program: &'b AccountInfo<'a>,
+    pub vault: &'b AccountInfo<'a>,
+    pub mint: &'b AccountInfo<'a>,
+    /// Temporary token account PDA at `[b"unwrap", fundraiser, recipient]`
+    pub temporary_account: &'b AccountInfo<'a>,
+    pub fundraiser: &'b AccountInfo<'a>,
+    pub recipient: &'b AccountInfo<'a>,
+    pub system_program: &'b AccountInfo<'a>,
+}
+
+/// Pays `amount` of wrapped SOL out of the vault as lamports. The tokens go through a
+/// temporary token account of the fundraiser at `[b"unwrap", fundraiser, recipient]`,
+/// closed to `recipient` straight away, so `recipient` gets the lamports back along with
+/// the rent it paid for the temporary account.
+pub fn unwrap_to(
+    accounts: &UnwrapAccounts,
+    amount: u64,
+    fundraiser_seeds: &[&[u8]]
+) -> ProgramResult {
+    let UnwrapAccounts { token_program, vault, mint, temporary_account, fundraiser, recipient, system_program } = *accounts;
+
+    if !is_native_mint(mint.key) {
+        return Err(FundraiserError::NotNativeMint.into())
+    }
+
+    let (temporary_pda, temporary_bump) = Pubkey::find_program_address(
+        &[b"unwrap", fundraiser.key.as_ref(), recipient.key.as_ref()],
+        &crate::ID
+    );
+
+    if temporary_pda != *temporary_account.key {
+        return Err(ProgramError::InvalidSeeds)
+    }
+
+    let temporary_seeds: &[&[u8]] = &[
+        b"unwrap",
+        fundraiser.key.as_ref(),
+        recipient.key.as_ref(),
+        &[temporary_bump]
+    ];
+
+    let len = account_len(token_program.key, &mint.try_borrow_data()?)?;
+    let lamports = Rent::get()?.minimum_balance(len);
+
+    // lamports sent to the address beforehand would make `create_account` fail
+    if temporary_account.lamports() == 0 {
+        invoke_signed(
+            &create_account(recipient.key, temporary_account.key, lamports, len as u64, token_program.key),
+            &[recipient.clone(), temporary_account.clone(), system_program.clone()],
+            &[temporary_seeds]
+        )?;
+    } else {
+        let missing = lamports.saturating_sub(temporary_account.lamports());
+
+        if missing > 0 {
+            invoke(
+                &transfer(recipient.key, temporary_account.key, missing),
+                &[recipient.clone(), temporary_account.clone(), system_program.clone()]
+            )?;
+        }
+
+        invoke_signed(
+            &allocate(temporary_account.key, len as u64),
+            &[temporary_account.clone(), system_program.clone()],
+            &[temporary_seeds]
+        )?;
+
+        invoke_signed(
+            &assign(temporary_account.key, token_program.key),
+            &[temporary_account.clone(), system_program.clone()],
+            &[temporary_seeds]
+        )?;
+    }
+
+    invoke(
+        &initialize_account3(token_program.key, temporary_account.key, mint.key, fundraiser.key)?,
+        &[temporary_account.clone(), mint.clone(), fundraiser.clone(), token_program.clone()]
+    )?;
+
+    // wrapped SOL has no transfer hook
+    transfer_checked(
+        &TransferAccounts {
+            token_program,
+            source: vault,
+            mint,
+            destination: temporary_account,
+            authority: fundraiser,
+            hook_accounts: &[],
+        },
+        amount,
+        &[fundraiser_seeds]
+    )?;
+
+    invoke_signed(
+        &close_account(token_program.key, temporary_account.key, recipient.key, fundraiser.key, &[])?,
+        &[temporary_account.clone(), recipient.clone(), fundraiser.clone(), token_program.clone()],
+        &[fundraiser_seeds]
+    )
+}
+
+/// Client side resolution of the accounts a transfer hook needs.
+#[cfg(not(target_os = "solana"))]
+pub mod offchain {
+    use std::future::Future;
+
+    use solana_program::{instruction::AccountMeta, pubkey::Pubkey};
+    use spl_token_2022::{
+        extension::{transfer_hook, StateWithExtensions},
+        state::Mint,
+    };
+    use spl_transfer_hook_interface::offchain::{
+        add_extra_account_metas_for_execute,
+        AccountDataResult,
+        AccountFetchError
+    };
+
+    /// Accounts to append after an instruction's own accounts so the mint's transfer hook
+    /// can run on a transfer of `amount` from `source` to `destination`, empty when the
+    /// mint has no hook. `fetch_account_data` returns `None` for missing accounts.
+    pub async fn resolve_hook_accounts<F, Fut>(
+        fetch_account_data: F,
+        mint: &Pubkey,
+        source: &Pubkey,
+        destination: &Pubkey,
+        authority: &Pubkey,
+        amount: u64
+    ) -> Result<Vec<AccountMeta>, AccountFetchError>
+    where
+        F: Fn(Pubkey) -> Fut,
+        Fut: Future<Output = AccountDataResult>,
+    {
+        let mint_data = fetch_account_data(*mint)
+            .await?
+            .ok_or("mint account not found")?;
+
+        let Some(hook_program_id) = transfer_hook::get_program_id(
+            &StateWithExtensions::<Mint>::unpack(&mint_data)?
+        ) else {
+            return Ok(vec![])
+        };
+
+        // the hook accounts come after source, mint, destination and authority
+        let mut transfer_ix = spl_token_2022::instruction::transfer_checked(
+            &spl_token_2022::ID, source, mint, destination, authority, &[], amount, 0
+        )?;
+
+        add_extra_account_metas_for_execute(
+            &mut transfer_ix,
+            &hook_program_id,
+            source,
+            mint,
+            destination,
+            authority,
+            amount,
+            fetch_account_data
+        ).await?;
+
+        Ok(transfer_ix.accounts.split_off(4))
+    }
+}